@@ -1,25 +1,33 @@
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
 use crate::db::{
-    create_cancellation_token, list_schemas, list_tables, migrate_tables, CancellationToken,
-    ConnectionConfig, ConnectionManagerHandle, ConnectionStatus, MigrationOptions, MigrationResult,
-    TableInfo, TableSchema,
+    check_target, create_cancellation_token, create_pause_token, dependency_order, estimate_tables,
+    get_row_count, list_schemas, list_tables, list_tables_sorted, migrate_tables, open_pool,
+    verify_tables, CancellationToken, ConnectionConfig, ConnectionManagerHandle, ConnectionStatus,
+    DatabaseSummary, DistinctValues, ExportFormat, MigrateError, MigrationEstimate,
+    MigrationOptions, MigrationResult, PauseToken, RelationKind, SchemaMap, TableInfo,
+    TablePreview, TableSchema, TableSelection, TargetReadiness, VerifyResult,
 };
 
-/// Application state holding connection manager and cancellation token
+/// Application state holding connection manager and cancellation tokens
 pub struct AppState {
     pub conn_manager: ConnectionManagerHandle,
-    pub cancel_token: RwLock<Option<CancellationToken>>,
+    pub cancel_tokens: RwLock<HashMap<String, CancellationToken>>, // keyed by migration or estimate id
+    pub pause_tokens: RwLock<HashMap<String, PauseToken>>,         // keyed by migration id
 }
 
 impl AppState {
     pub fn new(conn_manager: ConnectionManagerHandle) -> Self {
         Self {
             conn_manager,
-            cancel_token: RwLock::new(None),
+            cancel_tokens: RwLock::new(HashMap::new()),
+            pause_tokens: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -29,7 +37,32 @@ impl AppState {
 pub async fn connect_database(
     state: State<'_, Arc<AppState>>,
     config: ConnectionConfig,
-) -> Result<ConnectionStatus, String> {
+) -> Result<ConnectionStatus, MigrateError> {
+    state.conn_manager.connect(config).await
+}
+
+/// Connect to a PostgreSQL database using a connection URL
+#[tauri::command]
+pub async fn connect_database_url(
+    state: State<'_, Arc<AppState>>,
+    url: String,
+) -> Result<ConnectionStatus, MigrateError> {
+    let config = ConnectionConfig::from_url(&url)?;
+    state.conn_manager.connect(config).await
+}
+
+/// Connect to a PostgreSQL database defined in the libpq service file. Without a service name
+/// the `PGSERVICE` environment variable is used.
+#[tauri::command]
+pub async fn connect_database_service(
+    state: State<'_, Arc<AppState>>,
+    service: Option<String>,
+) -> Result<ConnectionStatus, MigrateError> {
+    let service = match service.filter(|s| !s.trim().is_empty()) {
+        Some(service) => service,
+        None => std::env::var("PGSERVICE").map_err(|_| "No service name given".to_string())?,
+    };
+    let config = ConnectionConfig::from_service(service.trim())?;
     state.conn_manager.connect(config).await
 }
 
@@ -38,23 +71,121 @@ pub async fn connect_database(
 pub async fn disconnect_database(
     state: State<'_, Arc<AppState>>,
     connection_id: String,
-) -> Result<(), String> {
+) -> Result<(), MigrateError> {
     state.conn_manager.disconnect(&connection_id).await
 }
 
+/// Disconnect every open connection
+#[tauri::command]
+pub async fn disconnect_all(state: State<'_, Arc<AppState>>) -> Result<(), MigrateError> {
+    state.conn_manager.disconnect_all().await;
+    Ok(())
+}
+
+/// Check a connection and re-establish it if the server dropped it
+#[tauri::command]
+pub async fn reconnect_database(
+    state: State<'_, Arc<AppState>>,
+    connection_id: String,
+) -> Result<ConnectionStatus, MigrateError> {
+    state.conn_manager.ping(&connection_id).await
+}
+
+/// List the connections currently held by the backend
+#[tauri::command]
+pub async fn get_connections(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ConnectionStatus>, MigrateError> {
+    Ok(state.conn_manager.list_connections().await)
+}
+
 /// List all tables in a database
 #[tauri::command]
 pub async fn get_tables(
     state: State<'_, Arc<AppState>>,
     connection_id: String,
-) -> Result<Vec<TableInfo>, String> {
+    estimated: Option<bool>,
+    dependency_sorted: Option<bool>,
+) -> Result<Vec<TableInfo>, MigrateError> {
     let pool = state
         .conn_manager
         .get_pool(&connection_id)
         .await
         .ok_or("Connection not found")?;
 
-    list_tables(&pool).await
+    // Optionally in migration order, so selecting everything needs no separate sort
+    if dependency_sorted.unwrap_or(false) {
+        list_tables_sorted(&pool, estimated.unwrap_or(false)).await
+    } else {
+        list_tables(&pool, estimated.unwrap_or(false)).await
+    }
+}
+
+/// Selections for every table of a schema, optionally leaving out empty ones
+#[tauri::command]
+pub async fn select_schema_tables(
+    state: State<'_, Arc<AppState>>,
+    connection_id: String,
+    schema: String,
+    exclude_empty: Option<bool>,
+) -> Result<Vec<TableSelection>, MigrateError> {
+    let pool = state
+        .conn_manager
+        .get_pool(&connection_id)
+        .await
+        .ok_or("Connection not found")?;
+
+    let mut selections = Vec::new();
+    for t in list_tables(&pool, true).await? {
+        if t.schema != schema || t.relation_kind == RelationKind::ForeignTable {
+            continue;
+        }
+        // The planner estimate is 0 for a table that was never analyzed, so count those
+        if exclude_empty.unwrap_or(false)
+            && t.row_count <= 0
+            && get_row_count(&pool, &t.schema, &t.name, None).await? == 0
+        {
+            continue;
+        }
+        selections.push(TableSelection {
+            schema: t.schema,
+            name: t.name,
+            ..Default::default()
+        });
+    }
+    Ok(selections)
+}
+
+/// Get the exact row count of a single table
+#[tauri::command]
+pub async fn get_table_row_count(
+    state: State<'_, Arc<AppState>>,
+    connection_id: String,
+    schema: String,
+    table: String,
+) -> Result<i64, MigrateError> {
+    let pool = state
+        .conn_manager
+        .get_pool(&connection_id)
+        .await
+        .ok_or("Connection not found")?;
+
+    get_row_count(&pool, &schema, &table, None).await
+}
+
+/// Get table count, estimated row total and total size of a database
+#[tauri::command]
+pub async fn get_database_summary(
+    state: State<'_, Arc<AppState>>,
+    connection_id: String,
+) -> Result<DatabaseSummary, MigrateError> {
+    let pool = state
+        .conn_manager
+        .get_pool(&connection_id)
+        .await
+        .ok_or("Connection not found")?;
+
+    crate::db::get_database_summary(&pool).await
 }
 
 /// List all schemas in a database
@@ -62,7 +193,7 @@ pub async fn get_tables(
 pub async fn get_schemas(
     state: State<'_, Arc<AppState>>,
     connection_id: String,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, MigrateError> {
     let pool = state
         .conn_manager
         .get_pool(&connection_id)
@@ -79,7 +210,7 @@ pub async fn get_table_schema(
     connection_id: String,
     schema: String,
     table: String,
-) -> Result<TableSchema, String> {
+) -> Result<TableSchema, MigrateError> {
     let pool = state
         .conn_manager
         .get_pool(&connection_id)
@@ -89,6 +220,44 @@ pub async fn get_table_schema(
     crate::db::get_table_schema(&pool, &schema, &table).await
 }
 
+/// Get the first rows of a table (at most 100), rendered as they would be migrated
+#[tauri::command]
+pub async fn preview_table(
+    state: State<'_, Arc<AppState>>,
+    connection_id: String,
+    schema: String,
+    table: String,
+    limit: Option<usize>,
+) -> Result<TablePreview, MigrateError> {
+    let pool = state
+        .conn_manager
+        .get_pool(&connection_id)
+        .await
+        .ok_or("Connection not found")?;
+
+    crate::db::preview_table(&pool, &schema, &table, limit.unwrap_or(20)).await
+}
+
+/// Get up to `limit` distinct values of a column (at most 1000), e.g. to pick filter values
+#[tauri::command]
+pub async fn get_column_distinct_values(
+    state: State<'_, Arc<AppState>>,
+    connection_id: String,
+    schema: String,
+    table: String,
+    column: String,
+    limit: Option<usize>,
+) -> Result<DistinctValues, MigrateError> {
+    let pool = state
+        .conn_manager
+        .get_pool(&connection_id)
+        .await
+        .ok_or("Connection not found")?;
+
+    crate::db::get_column_distinct_values(&pool, &schema, &table, &column, limit.unwrap_or(100))
+        .await
+}
+
 /// Request to migrate tables
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MigrateTablesRequest {
@@ -97,12 +266,22 @@ pub struct MigrateTablesRequest {
     pub tables: Vec<TableSelection>,
     pub options: MigrationOptions,
     pub target_schema_override: Option<String>,
+    /// Source schema -> target schema, for schemas going somewhere else than
+    /// `target_schema_override` (or their own name)
+    #[serde(default)]
+    pub schema_map: HashMap<String, String>,
+    /// JSON file recording completed tables so an interrupted migration can resume
+    #[serde(default)]
+    pub checkpoint_path: Option<String>,
+    /// Id used to cancel this migration; generated when not supplied
+    #[serde(default)]
+    pub migration_id: Option<String>,
 }
 
+/// Emitted as soon as a migration is registered, before any table is migrated
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TableSelection {
-    pub schema: String,
-    pub name: String,
+pub struct MigrationStarted {
+    pub migration_id: String,
 }
 
 /// Start table migration
@@ -111,7 +290,53 @@ pub async fn start_migration(
     app_handle: AppHandle,
     state: State<'_, Arc<AppState>>,
     request: MigrateTablesRequest,
-) -> Result<MigrationResult, String> {
+) -> Result<MigrationResult, MigrateError> {
+    // Connections may have been dropped by the server while the app sat idle
+    state
+        .conn_manager
+        .ping(&request.source_connection_id)
+        .await
+        .map_err(|e| e.context("Source connection"))?;
+    state
+        .conn_manager
+        .ping(&request.target_connection_id)
+        .await
+        .map_err(|e| e.context("Target connection"))?;
+
+    if state
+        .conn_manager
+        .is_read_only(&request.target_connection_id)
+        .await
+    {
+        return Err(MigrateError::Other(
+            "The target connection is read only. Reconnect without read only to migrate into it."
+                .to_string(),
+        ));
+    }
+
+    // Reading and writing the same table would loop over the rows it inserts
+    if state
+        .conn_manager
+        .same_database(&request.source_connection_id, &request.target_connection_id)
+        .await
+    {
+        let schema_map = SchemaMap::new(
+            request.target_schema_override.clone(),
+            request.schema_map.clone(),
+        );
+        let same_table = request.tables.iter().find(|t| {
+            schema_map.target_schema(&t.schema) == t.schema
+                && t.target_table() == t.name
+                && t.source_query.is_none()
+        });
+        if let Some(t) = same_table {
+            return Err(MigrateError::Other(format!(
+                "Source and target are the same database, so {}.{} would be copied onto itself. Set a different target schema or target table name.",
+                t.schema, t.name
+            )));
+        }
+    }
+
     let source_pool = state
         .conn_manager
         .get_pool(&request.source_connection_id)
@@ -124,66 +349,316 @@ pub async fn start_migration(
         .await
         .ok_or("Target connection not found")?;
 
-    // Create cancellation token
+    // Register a cancellation token for this migration
+    let migration_id = request
+        .migration_id
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
     let cancel_token = create_cancellation_token();
     {
-        let mut token = state.cancel_token.write().await;
-        *token = Some(cancel_token.clone());
+        let mut tokens = state.cancel_tokens.write().await;
+        if tokens.contains_key(&migration_id) {
+            return Err(MigrateError::Other(format!(
+                "Migration {} is already running",
+                migration_id
+            )));
+        }
+        tokens.insert(migration_id.clone(), cancel_token.clone());
     }
-
-    let tables: Vec<(String, String)> = request
-        .tables
-        .iter()
-        .map(|t| (t.schema.clone(), t.name.clone()))
-        .collect();
-
-    let result = migrate_tables(
+    let pause_token = create_pause_token();
+    state
+        .pause_tokens
+        .write()
+        .await
+        .insert(migration_id.clone(), pause_token.clone());
+    let _ = app_handle.emit(
+        "migration-started",
+        &MigrationStarted {
+            migration_id: migration_id.clone(),
+        },
+    );
+
+    let mut result = migrate_tables(
         app_handle,
         &source_pool,
         &target_pool,
-        tables,
+        request.tables,
         request.options,
         cancel_token,
-        request.target_schema_override,
+        pause_token,
+        SchemaMap::new(request.target_schema_override, request.schema_map),
+        request.checkpoint_path,
     )
     .await;
 
-    // Clear cancellation token
+    // Clear cancellation and pause tokens
+    state.cancel_tokens.write().await.remove(&migration_id);
+    state.pause_tokens.write().await.remove(&migration_id);
+
+    result.migration_id = migration_id;
+    Ok(result)
+}
+
+/// Request to export tables to files instead of a target database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportTablesRequest {
+    pub source_connection_id: String,
+    pub tables: Vec<TableSelection>,
+    /// Directory the files are written to, one per table
+    pub output_dir: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Gzip each file, e.g. `public.users.csv.gz`
+    #[serde(default)]
+    pub compress: bool,
+    #[serde(default)]
+    pub options: MigrationOptions,
+    /// Id used to cancel this export; generated when not supplied
+    #[serde(default)]
+    pub migration_id: Option<String>,
+}
+
+/// Export tables to CSV or NDJSON files
+#[tauri::command]
+pub async fn export_tables(
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    request: ExportTablesRequest,
+) -> Result<MigrationResult, MigrateError> {
+    let source_pool = state
+        .conn_manager
+        .get_pool(&request.source_connection_id)
+        .await
+        .ok_or("Source connection not found")?;
+
+    let migration_id = request
+        .migration_id
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let cancel_token = create_cancellation_token();
     {
-        let mut token = state.cancel_token.write().await;
-        *token = None;
+        let mut tokens = state.cancel_tokens.write().await;
+        if tokens.contains_key(&migration_id) {
+            return Err(MigrateError::Other(format!(
+                "Migration {} is already running",
+                migration_id
+            )));
+        }
+        tokens.insert(migration_id.clone(), cancel_token.clone());
     }
+    let _ = app_handle.emit(
+        "migration-started",
+        &MigrationStarted {
+            migration_id: migration_id.clone(),
+        },
+    );
+
+    let mut result = crate::db::export_tables(
+        app_handle,
+        &source_pool,
+        request.tables,
+        request.options,
+        std::path::Path::new(&request.output_dir),
+        request.format,
+        request.compress,
+        cancel_token,
+    )
+    .await;
 
+    state.cancel_tokens.write().await.remove(&migration_id);
+
+    result.migration_id = migration_id;
     Ok(result)
 }
 
-/// Cancel ongoing migration
+/// Request to verify migrated tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyMigrationRequest {
+    pub source_connection_id: String,
+    pub target_connection_id: String,
+    pub tables: Vec<TableSelection>,
+    pub target_schema_override: Option<String>,
+    #[serde(default)]
+    pub schema_map: HashMap<String, String>,
+    /// Also compare an md5 checksum of each table's rows (slower; needs a primary key)
+    #[serde(default)]
+    pub checksum: bool,
+}
+
+/// Compare source and target row counts of migrated tables
+#[tauri::command]
+pub async fn verify_migration(
+    state: State<'_, Arc<AppState>>,
+    request: VerifyMigrationRequest,
+) -> Result<Vec<VerifyResult>, MigrateError> {
+    let source_pool = state
+        .conn_manager
+        .get_pool(&request.source_connection_id)
+        .await
+        .ok_or("Source connection not found")?;
+
+    let target_pool = state
+        .conn_manager
+        .get_pool(&request.target_connection_id)
+        .await
+        .ok_or("Target connection not found")?;
+
+    Ok(verify_tables(
+        &source_pool,
+        &target_pool,
+        &request.tables,
+        &SchemaMap::new(request.target_schema_override, request.schema_map),
+        request.checksum,
+    )
+    .await)
+}
+
+/// Request to estimate a migration before starting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimateMigrationRequest {
+    pub source_connection_id: String,
+    pub tables: Vec<TableSelection>,
+    #[serde(default)]
+    pub options: MigrationOptions,
+    /// Id used to cancel the estimate with `cancel_migration`; generated when not supplied
+    #[serde(default)]
+    pub estimate_id: Option<String>,
+}
+
+/// Estimate rows and bytes to copy and flag columns with unsupported types
 #[tauri::command]
-pub async fn cancel_migration(state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    let token = state.cancel_token.read().await;
-    if let Some(ref t) = *token {
+pub async fn estimate_migration(
+    state: State<'_, Arc<AppState>>,
+    request: EstimateMigrationRequest,
+) -> Result<MigrationEstimate, MigrateError> {
+    let source_pool = state
+        .conn_manager
+        .get_pool(&request.source_connection_id)
+        .await
+        .ok_or("Source connection not found")?;
+
+    let estimate_id = request
+        .estimate_id
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let cancel_token = create_cancellation_token();
+    {
+        let mut tokens = state.cancel_tokens.write().await;
+        if tokens.contains_key(&estimate_id) {
+            return Err(MigrateError::Other(format!(
+                "Estimate {} is already running",
+                estimate_id
+            )));
+        }
+        tokens.insert(estimate_id.clone(), cancel_token.clone());
+    }
+
+    let estimate = estimate_tables(
+        &source_pool,
+        &request.tables,
+        &request.options,
+        &cancel_token,
+    )
+    .await;
+
+    state.cancel_tokens.write().await.remove(&estimate_id);
+    estimate
+}
+
+/// Request to check the target before migrating
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckTargetRequest {
+    pub target_connection_id: String,
+    pub tables: Vec<TableSelection>,
+    #[serde(default)]
+    pub options: MigrationOptions,
+    pub target_schema_override: Option<String>,
+    #[serde(default)]
+    pub schema_map: HashMap<String, String>,
+}
+
+/// Check that the target is writable and the role has the privileges the migration needs
+#[tauri::command]
+pub async fn check_target_readiness(
+    state: State<'_, Arc<AppState>>,
+    request: CheckTargetRequest,
+) -> Result<TargetReadiness, MigrateError> {
+    let target_pool = state
+        .conn_manager
+        .get_pool(&request.target_connection_id)
+        .await
+        .ok_or("Target connection not found")?;
+
+    check_target(
+        &target_pool,
+        &request.tables,
+        &request.options,
+        &SchemaMap::new(request.target_schema_override, request.schema_map),
+    )
+    .await
+}
+
+/// Pause an ongoing migration after its current batches; the tables being loaded keep their
+/// connections (and open transactions) while paused
+#[tauri::command]
+pub async fn pause_migration(
+    state: State<'_, Arc<AppState>>,
+    migration_id: String,
+) -> Result<(), MigrateError> {
+    set_paused(&state, &migration_id, true).await
+}
+
+/// Resume a paused migration
+#[tauri::command]
+pub async fn resume_migration(
+    state: State<'_, Arc<AppState>>,
+    migration_id: String,
+) -> Result<(), MigrateError> {
+    set_paused(&state, &migration_id, false).await
+}
+
+async fn set_paused(
+    state: &AppState,
+    migration_id: &str,
+    paused: bool,
+) -> Result<(), MigrateError> {
+    let tokens = state.pause_tokens.read().await;
+    match tokens.get(migration_id) {
+        Some(t) => {
+            t.store(paused, std::sync::atomic::Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err(MigrateError::Other(format!(
+            "No migration in progress with id {}",
+            migration_id
+        ))),
+    }
+}
+
+/// Cancel an ongoing migration
+#[tauri::command]
+pub async fn cancel_migration(
+    state: State<'_, Arc<AppState>>,
+    migration_id: String,
+) -> Result<(), MigrateError> {
+    let tokens = state.cancel_tokens.read().await;
+    if let Some(t) = tokens.get(&migration_id) {
         t.store(true, std::sync::atomic::Ordering::Relaxed);
         Ok(())
     } else {
-        Err("No migration in progress".to_string())
+        Err(MigrateError::Other(format!(
+            "No migration in progress with id {}",
+            migration_id
+        )))
     }
 }
 
 /// Test database connection without storing it
 #[tauri::command]
-pub async fn test_connection(config: ConnectionConfig) -> Result<bool, String> {
-    use sqlx::postgres::PgPoolOptions;
-
-    let pool = PgPoolOptions::new()
-        .max_connections(1)
-        .connect(&config.connection_string())
-        .await
-        .map_err(|e| format!("Connection failed: {}", e))?;
-
-    sqlx::query("SELECT 1")
-        .execute(&pool)
-        .await
-        .map_err(|e| format!("Query failed: {}", e))?;
+pub async fn test_connection(config: ConnectionConfig) -> Result<bool, MigrateError> {
+    // Goes through the SSH tunnel (if configured), which stays open until the test finishes
+    let (pool, _tunnel) = open_pool(&ConnectionConfig {
+        max_connections: Some(1),
+        ..config
+    })
+    .await?;
 
     pool.close().await;
     Ok(true)
@@ -194,7 +669,7 @@ pub async fn test_connection(config: ConnectionConfig) -> Result<bool, String> {
 pub struct SchemaDiff {
     pub schema: String,
     pub table: String,
-    pub status: String, // "MATCH", "MISSING_IN_TARGET", "COLUMNS_MISMATCH"
+    pub status: String, // "MATCH", "WARN", "MISSING_IN_TARGET", "COLUMNS_MISMATCH", "ERROR"
     pub details: Option<String>,
 }
 
@@ -205,7 +680,33 @@ pub async fn analyze_schema(
     source_connection_id: String,
     target_connection_id: String,
     tables: Vec<TableSelection>,
-) -> Result<Vec<SchemaDiff>, String> {
+) -> Result<Vec<SchemaDiff>, MigrateError> {
+    let source_pool = state
+        .conn_manager
+        .get_pool(&source_connection_id)
+        .await
+        .ok_or("Source connection not found")?;
+
+    let target_pool = state
+        .conn_manager
+        .get_pool(&target_connection_id)
+        .await
+        .ok_or("Target connection not found")?;
+
+    compare_schemas(&source_pool, &target_pool, tables).await
+}
+
+/// Create and fill the tables of a source schema that don't exist in the target yet, leaving
+/// the tables already there untouched
+#[tauri::command]
+pub async fn migrate_missing_tables(
+    app_handle: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    source_connection_id: String,
+    target_connection_id: String,
+    schema: String,
+    options: Option<MigrationOptions>,
+) -> Result<MigrationResult, MigrateError> {
     let source_pool = state
         .conn_manager
         .get_pool(&source_connection_id)
@@ -218,92 +719,200 @@ pub async fn analyze_schema(
         .await
         .ok_or("Target connection not found")?;
 
+    // In dependency order, so referenced tables are created first
+    let candidates: Vec<TableSelection> = list_tables_sorted(&source_pool, true)
+        .await?
+        .into_iter()
+        .filter(|t| t.schema == schema && t.relation_kind != RelationKind::ForeignTable)
+        .map(|t| TableSelection {
+            schema: t.schema,
+            name: t.name,
+            ..Default::default()
+        })
+        .collect();
+    let missing: Vec<(String, String)> =
+        compare_schemas(&source_pool, &target_pool, candidates.clone())
+            .await?
+            .into_iter()
+            .filter(|d| d.status == "MISSING_IN_TARGET")
+            .map(|d| (d.schema, d.table))
+            .collect();
+    let tables: Vec<TableSelection> = candidates
+        .into_iter()
+        .filter(|t| missing.contains(&(t.schema.clone(), t.name.clone())))
+        .collect();
+
+    // Also identifies the result when there is nothing to migrate
+    let migration_id = Uuid::new_v4().to_string();
+    if tables.is_empty() {
+        return Ok(MigrationResult {
+            success: true,
+            tables_migrated: 0,
+            total_rows: 0,
+            errors: Vec::new(),
+            elapsed_ms: 0,
+            table_results: Vec::new(),
+            migration_id,
+        });
+    }
+
+    // The tables are new, so they're always created; nothing on the target is emptied
+    let options = MigrationOptions {
+        create_table_if_not_exists: true,
+        recreate_table: false,
+        truncate_before_insert: false,
+        schema_only: false,
+        ..options.unwrap_or_default()
+    };
+    start_migration(
+        app_handle,
+        state,
+        MigrateTablesRequest {
+            source_connection_id,
+            target_connection_id,
+            tables,
+            options,
+            target_schema_override: None,
+            schema_map: HashMap::new(),
+            checkpoint_path: None,
+            migration_id: Some(migration_id),
+        },
+    )
+    .await
+}
+
+/// Diff the selected tables' columns between source and target
+async fn compare_schemas(
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    tables: Vec<TableSelection>,
+) -> Result<Vec<SchemaDiff>, MigrateError> {
+    // Both sides are read in a few batched queries rather than per table
+    let keys: Vec<(String, String)> = tables
+        .iter()
+        .map(|t| (t.schema.clone(), t.name.clone()))
+        .collect();
+    let source_schemas = crate::db::get_table_schemas(source_pool, keys.clone())
+        .await
+        .map_err(|e| e.context("Failed to read source schema"))?;
+    let target_schemas = crate::db::get_table_schemas(target_pool, keys).await?;
+
     let mut diffs = Vec::new();
 
     for t in tables {
-        let source_schema = crate::db::get_table_schema(&source_pool, &t.schema, &t.name).await;
-
-        match source_schema {
-            Ok(s_schema) => {
-                // Check if exists in target
-                // Note: We might want to handle target_schema_override logic here too eventually
-                let target_schema =
-                    crate::db::get_table_schema(&target_pool, &t.schema, &t.name).await;
-
-                match target_schema {
-                    Ok(t_schema) => {
-                        // Compare columns
-                        let mut mismatch_details = Vec::new();
-
-                        // Check for missing columns in target
-                        for s_col in &s_schema.columns {
-                            let t_col = t_schema.columns.iter().find(|c| c.name == s_col.name);
-                            match t_col {
-                                Some(tc) => {
-                                    if s_col.data_type != tc.data_type {
-                                        mismatch_details.push(format!(
-                                            "Column '{}' type mismatch: {} vs {}",
-                                            s_col.name, s_col.data_type, tc.data_type
-                                        ));
-                                    }
-                                    if s_col.is_nullable != tc.is_nullable {
-                                        // Warning only?
-                                    }
-                                }
-                                None => {
-                                    mismatch_details
-                                        .push(format!("Column '{}' missing in target", s_col.name));
-                                }
-                            }
-                        }
-
-                        if mismatch_details.is_empty() {
-                            diffs.push(SchemaDiff {
-                                schema: t.schema,
-                                table: t.name,
-                                status: "MATCH".to_string(),
-                                details: None,
-                            });
-                        } else {
-                            diffs.push(SchemaDiff {
-                                schema: t.schema,
-                                table: t.name,
-                                status: "COLUMNS_MISMATCH".to_string(),
-                                details: Some(mismatch_details.join(", ")),
-                            });
-                        }
+        let is_table = |s: &&TableSchema| s.schema_name == t.schema && s.table_name == t.name;
+        let Some(s_schema) = source_schemas.iter().find(is_table) else {
+            diffs.push(SchemaDiff {
+                schema: t.schema,
+                table: t.name,
+                status: "ERROR".to_string(),
+                details: Some("Table does not exist in source database".to_string()),
+            });
+            continue;
+        };
+        // Note: We might want to handle target_schema_override logic here too eventually
+        let Some(t_schema) = target_schemas.iter().find(is_table) else {
+            diffs.push(SchemaDiff {
+                schema: t.schema,
+                table: t.name,
+                status: "MISSING_IN_TARGET".to_string(),
+                details: Some("Table does not exist in target database".to_string()),
+            });
+            continue;
+        };
+
+        // Compare columns. Mismatches make the copy fail; warnings are differences it survives
+        // (or that only fail on some data, like NULLs in a column that is NOT NULL in target)
+        let mut mismatch_details = Vec::new();
+        let mut warning_details = Vec::new();
+
+        for s_col in &s_schema.columns {
+            let t_col = t_schema.columns.iter().find(|c| c.name == s_col.name);
+            match t_col {
+                Some(tc) => {
+                    if s_col.data_type != tc.data_type {
+                        mismatch_details.push(format!(
+                            "Type mismatch: column '{}' is {} in source, {} in target",
+                            s_col.name, s_col.data_type, tc.data_type
+                        ));
                     }
-                    Err(_) => {
-                        diffs.push(SchemaDiff {
-                            schema: t.schema,
-                            table: t.name,
-                            status: "MISSING_IN_TARGET".to_string(),
-                            details: Some("Table does not exist in target database".to_string()),
-                        });
+                    if s_col.is_nullable != tc.is_nullable {
+                        warning_details.push(format!(
+                            "Nullability differs: column '{}' is {} in source, {} in target",
+                            s_col.name,
+                            nullability(s_col.is_nullable),
+                            nullability(tc.is_nullable)
+                        ));
                     }
+                    if s_col.column_default != tc.column_default {
+                        warning_details.push(format!(
+                            "Default differs: column '{}' defaults to {} in source, {} in target",
+                            s_col.name,
+                            s_col.column_default.as_deref().unwrap_or("nothing"),
+                            tc.column_default.as_deref().unwrap_or("nothing")
+                        ));
+                    }
+                }
+                None => {
+                    mismatch_details.push(format!("Missing in target: column '{}'", s_col.name));
                 }
             }
-            Err(e) => {
-                diffs.push(SchemaDiff {
-                    schema: t.schema,
-                    table: t.name,
-                    status: "ERROR".to_string(),
-                    details: Some(format!("Failed to read source schema: {}", e)),
-                });
+        }
+
+        // Target-only columns are filled from their default, which fails when they have none
+        // and are NOT NULL
+        for tc in &t_schema.columns {
+            if s_schema.columns.iter().any(|c| c.name == tc.name) {
+                continue;
+            }
+            let fillable = tc.is_nullable
+                || tc.column_default.is_some()
+                || tc.identity_generation.is_some()
+                || tc.generation_expression.is_some();
+            if fillable {
+                warning_details.push(format!("Extra in target: column '{}'", tc.name));
+            } else {
+                mismatch_details.push(format!(
+                    "Extra in target: column '{}' is NOT NULL without a default",
+                    tc.name
+                ));
             }
         }
+
+        let status = if !mismatch_details.is_empty() {
+            "COLUMNS_MISMATCH"
+        } else if !warning_details.is_empty() {
+            "WARN"
+        } else {
+            "MATCH"
+        };
+        mismatch_details.extend(warning_details);
+        diffs.push(SchemaDiff {
+            schema: t.schema,
+            table: t.name,
+            status: status.to_string(),
+            details: (!mismatch_details.is_empty()).then(|| mismatch_details.join(", ")),
+        });
     }
 
     Ok(diffs)
 }
 
+fn nullability(is_nullable: bool) -> &'static str {
+    if is_nullable {
+        "nullable"
+    } else {
+        "NOT NULL"
+    }
+}
+
 /// Sort tables based on Foreign Key dependencies
 #[tauri::command]
 pub async fn sort_tables_by_dependency(
     state: State<'_, Arc<AppState>>,
     connection_id: String,
     tables: Vec<TableSelection>,
-) -> Result<Vec<TableSelection>, String> {
+) -> Result<Vec<TableSelection>, MigrateError> {
     let pool = state
         .conn_manager
         .get_pool(&connection_id)
@@ -312,101 +921,13 @@ pub async fn sort_tables_by_dependency(
 
     let all_deps = crate::db::get_all_dependencies(&pool).await?;
 
-    // Filter deps to only include selected tables
-    // We only care if Table A depends on Table B AND both are in the selection list.
-
-    // Build Graph: Adjacency List
-    // key: (schema, table), value: list of dependencies (parents)
-    let mut graph: std::collections::HashMap<(String, String), Vec<(String, String)>> =
-        std::collections::HashMap::new();
-    let selected_set: std::collections::HashSet<(String, String)> = tables
-        .iter()
-        .map(|t| (t.schema.clone(), t.name.clone()))
-        .collect();
-
-    // Initialize graph with all selected tables
-    for t in &tables {
-        graph.insert((t.schema.clone(), t.name.clone()), Vec::new());
-    }
-
-    // Populate edges
-    for dep in all_deps {
-        if selected_set.contains(&(dep.schema.clone(), dep.name.clone())) {
-            for parent in dep.depends_on {
-                if selected_set.contains(&parent) {
-                    // Add edge: Node -> Parent
-                    if let Some(deps) = graph.get_mut(&(dep.schema.clone(), dep.name.clone())) {
-                        deps.push(parent);
-                    }
-                }
-            }
-        }
-    }
-
-    // Topological Sort (Kahn's Algorithm adaptation or simple DFS)
-    // We want to migrate PARENTS first.
-    // So if A depends on B, B comes before A.
-
-    let mut sorted_tables = Vec::new();
-    let mut visited = std::collections::HashSet::new();
-    let mut temp_visited = std::collections::HashSet::new(); // for cycle detection
-
-    // Recursive Visit function
-    fn visit(
-        node: &(String, String),
-        graph: &std::collections::HashMap<(String, String), Vec<(String, String)>>,
-        visited: &mut std::collections::HashSet<(String, String)>,
-        temp_visited: &mut std::collections::HashSet<(String, String)>,
-        sorted: &mut Vec<TableSelection>,
-    ) {
-        if visited.contains(node) {
-            return;
-        }
-        if temp_visited.contains(node) {
-            // Cycle detected! Just treat as visited to break loop,
-            // but ideally we should warn. For migration, we just output one.
-            return;
-        }
-
-        temp_visited.insert(node.clone());
-
-        if let Some(parents) = graph.get(node) {
-            for parent in parents {
-                visit(parent, graph, visited, temp_visited, sorted);
-            }
-        }
-
-        temp_visited.remove(node);
-        visited.insert(node.clone());
-        sorted.push(TableSelection {
-            schema: node.0.clone(),
-            name: node.1.clone(),
-        });
-    }
-
-    // The generic Topological Sort usually gives parents last if we do post-order traversal?
-    // Wait: Post-order DFS gives: [Leaf, ..., Root].
-    // If A depends on B (A -> B), we want B then A.
-    // My graph is: A has edge to B.
-    // visiting A -> visit B -> B has no deps -> push B. Then push A.
-    // So Result is [B, A]. This is CORRECT for migration (B created first).
-
-    // Make sure ordering is deterministic for non-dependent tables (alphabetical)
-    let mut nodes: Vec<(String, String)> = tables
+    // Parents first, so that if A depends on B, B is migrated before A
+    let keys: Vec<(String, String)> = tables
         .iter()
         .map(|t| (t.schema.clone(), t.name.clone()))
         .collect();
-    nodes.sort();
-
-    for node in nodes {
-        visit(
-            &node,
-            &graph,
-            &mut visited,
-            &mut temp_visited,
-            &mut sorted_tables,
-        );
-    }
-
-    Ok(sorted_tables)
+    Ok(dependency_order(&keys, &all_deps)
+        .into_iter()
+        .map(|i| tables[i].clone())
+        .collect())
 }
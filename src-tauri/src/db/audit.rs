@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+use super::migrate::TableResult;
+
+/// Something recorded in the audit log
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Validation passed and tables are about to be migrated
+    Started { tables: usize, dry_run: bool },
+    /// A table finished, successfully or not
+    Table(TableResult),
+    /// Every table finished (or the migration was cancelled)
+    Finished {
+        success: bool,
+        tables_migrated: usize,
+        total_rows: i64,
+        elapsed_ms: u64,
+        errors: Vec<String>,
+    },
+}
+
+/// One line of the audit log
+#[derive(Serialize)]
+struct AuditLine<'a> {
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+}
+
+/// Append-only JSONL record of what a migration moved and when. Unlike the checkpoint it is
+/// never rewritten or removed, so it survives crashes and later runs.
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Open an audit log for appending, creating it if it doesn't exist yet
+    pub fn open(path: &str) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append an event stamped with the current time, synced to disk before returning
+    pub fn record(&self, event: &AuditEvent) -> Result<(), String> {
+        let line = serde_json::to_string(&AuditLine {
+            timestamp: Utc::now(),
+            event,
+        })
+        .map_err(|e| format!("Failed to serialize audit log entry: {}", e))?;
+
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{}", line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("Failed to write audit log: {}", e))
+    }
+}
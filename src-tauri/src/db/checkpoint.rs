@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Progress of a single target table in a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCheckpoint {
+    pub schema: String,
+    pub table: String,
+    pub completed: bool,
+    pub last_pk_value: Option<String>, // SQL literal of the last migrated primary key
+}
+
+/// Checkpoint file contents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub tables: Vec<TableCheckpoint>,
+}
+
+/// Checkpoint persisted to disk so an interrupted migration can be resumed
+pub struct CheckpointFile {
+    path: PathBuf,
+    state: Mutex<Checkpoint>,
+}
+
+impl CheckpointFile {
+    /// Open a checkpoint file, starting empty if it doesn't exist yet
+    pub fn open(path: &str) -> Result<Self, String> {
+        let path = PathBuf::from(path);
+        let state = if path.exists() {
+            let data = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read checkpoint: {}", e))?;
+            serde_json::from_str(&data).map_err(|e| format!("Invalid checkpoint file: {}", e))?
+        } else {
+            Checkpoint::default()
+        };
+
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Whether a table was fully migrated in a previous run
+    pub fn is_completed(&self, schema: &str, table: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .tables
+            .iter()
+            .any(|t| t.schema == schema && t.table == table && t.completed)
+    }
+
+    /// Keyset position to resume a partially migrated table from
+    pub fn resume_position(&self, schema: &str, table: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .tables
+            .iter()
+            .find(|t| t.schema == schema && t.table == table && !t.completed)
+            .and_then(|t| t.last_pk_value.clone())
+    }
+
+    /// Record the last migrated primary key of a table
+    pub fn record_position(
+        &self,
+        schema: &str,
+        table: &str,
+        last_pk_value: &str,
+    ) -> Result<(), String> {
+        self.update(schema, table, |entry| {
+            entry.last_pk_value = Some(last_pk_value.to_string());
+        })
+    }
+
+    /// Mark a table as fully migrated
+    pub fn mark_completed(&self, schema: &str, table: &str) -> Result<(), String> {
+        self.update(schema, table, |entry| {
+            entry.completed = true;
+        })
+    }
+
+    /// Delete the checkpoint file (after a fully successful migration)
+    pub fn remove(&self) -> Result<(), String> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)
+                .map_err(|e| format!("Failed to remove checkpoint: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn update<F: FnOnce(&mut TableCheckpoint)>(
+        &self,
+        schema: &str,
+        table: &str,
+        apply: F,
+    ) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let pos = match state
+            .tables
+            .iter()
+            .position(|t| t.schema == schema && t.table == table)
+        {
+            Some(pos) => pos,
+            None => {
+                state.tables.push(TableCheckpoint {
+                    schema: schema.to_string(),
+                    table: table.to_string(),
+                    completed: false,
+                    last_pk_value: None,
+                });
+                state.tables.len() - 1
+            }
+        };
+        apply(&mut state.tables[pos]);
+
+        self.write(&state)
+    }
+
+    /// Write atomically: write a temp file next to the target, then rename over it
+    fn write(&self, state: &Checkpoint) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(state)
+            .map_err(|e| format!("Failed to serialize checkpoint: {}", e))?;
+
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+
+        std::fs::write(&tmp_path, data)
+            .map_err(|e| format!("Failed to write checkpoint: {}", e))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .map_err(|e| format!("Failed to write checkpoint: {}", e))?;
+
+        Ok(())
+    }
+}
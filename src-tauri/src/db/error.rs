@@ -0,0 +1,116 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Error returned by database operations and Tauri commands.
+/// Serialized as `{ kind, message, ... }` so the UI can branch on `kind`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrateError {
+    /// The server couldn't be reached or the connection dropped
+    Connection(String),
+    /// The role lacks a privilege (SQLSTATE 42501)
+    Permission(String),
+    /// A column type that can't be read or written
+    UnsupportedType { column: String, data_type: String },
+    /// Any other error reported by the server
+    Sql(String),
+    /// Stopped by the user
+    Cancelled,
+    /// Invalid input, configuration or local I/O
+    Other(String),
+}
+
+impl MigrateError {
+    /// Classify a sqlx error, prefixing its message with `context`
+    pub fn sql(context: &str, error: sqlx::Error) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error {
+            sqlx::Error::Database(ref db) => match db.code().as_deref() {
+                Some("42501") => MigrateError::Permission(message),
+                // Class 08: connection exception; 28: authentication failed;
+                // 57P01-57P03: server shutting down
+                Some(code)
+                    if code.starts_with("08")
+                        || code.starts_with("28")
+                        || code.starts_with("57P") =>
+                {
+                    MigrateError::Connection(message)
+                }
+                _ => MigrateError::Sql(message),
+            },
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => MigrateError::Connection(message),
+            _ => MigrateError::Sql(message),
+        }
+    }
+
+    /// Same error with `context` prepended to its message
+    pub fn context(self, context: &str) -> Self {
+        match self {
+            MigrateError::Connection(m) => MigrateError::Connection(format!("{}: {}", context, m)),
+            MigrateError::Permission(m) => MigrateError::Permission(format!("{}: {}", context, m)),
+            MigrateError::Sql(m) => MigrateError::Sql(format!("{}: {}", context, m)),
+            MigrateError::Other(m) => MigrateError::Other(format!("{}: {}", context, m)),
+            other => other,
+        }
+    }
+
+    /// Stable name of the variant, used as `kind` in JSON
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MigrateError::Connection(_) => "connection",
+            MigrateError::Permission(_) => "permission",
+            MigrateError::UnsupportedType { .. } => "unsupported_type",
+            MigrateError::Sql(_) => "sql",
+            MigrateError::Cancelled => "cancelled",
+            MigrateError::Other(_) => "other",
+        }
+    }
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::Connection(m)
+            | MigrateError::Permission(m)
+            | MigrateError::Sql(m)
+            | MigrateError::Other(m) => write!(f, "{}", m),
+            MigrateError::UnsupportedType { column, data_type } => write!(
+                f,
+                "Unsupported or unreadable data type '{}' for column '{}'",
+                data_type, column
+            ),
+            MigrateError::Cancelled => write!(f, "Migration cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+impl Serialize for MigrateError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("MigrateError", 4)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let MigrateError::UnsupportedType { column, data_type } = self {
+            state.serialize_field("column", column)?;
+            state.serialize_field("data_type", data_type)?;
+        }
+        state.end()
+    }
+}
+
+impl From<String> for MigrateError {
+    fn from(message: String) -> Self {
+        MigrateError::Other(message)
+    }
+}
+
+impl From<&str> for MigrateError {
+    fn from(message: &str) -> Self {
+        MigrateError::Other(message.to_string())
+    }
+}
@@ -0,0 +1,257 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::error::MigrateError;
+use super::schema::ColumnInfo;
+
+/// File format written by `export_tables`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row. NULL is an empty field and an empty string is
+    /// `""`, as in PostgreSQL's `COPY ... CSV`.
+    #[default]
+    Csv,
+    /// One JSON object per line, keyed by column name
+    Ndjson,
+    /// PostgreSQL's `COPY` text format, loadable with `COPY ... FROM` or psql's `\copy`:
+    /// tab-separated, `\N` for NULL, backslashes and line breaks escaped. No header row.
+    Copy,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Copy => "copy",
+        }
+    }
+}
+
+/// The export file, optionally gzip-compressed
+enum Sink {
+    Plain(File),
+    Gzip(GzEncoder<File>),
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Sink::Plain(file) => file.write(buf),
+            Sink::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Sink::Plain(file) => file.flush(),
+            Sink::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Writes one table's rows to `<schema>.<table>.<extension>` (plus `.gz` when compressed) in
+/// the output directory. Dropped without `finish`, e.g. on an error or cancellation, the
+/// buffered rows are still written and the gzip stream is ended, so the partial file can be
+/// read.
+pub struct ExportWriter {
+    format: ExportFormat,
+    columns: Vec<ColumnInfo>,
+    path: PathBuf,
+    out: BufWriter<Sink>,
+}
+
+impl ExportWriter {
+    /// Create (or overwrite) the table's file; `columns` are named as they should appear in it
+    pub fn create(
+        dir: &Path,
+        schema: &str,
+        table: &str,
+        columns: Vec<ColumnInfo>,
+        format: ExportFormat,
+        compress: bool,
+    ) -> Result<Self, MigrateError> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(format!(
+            "{}.{}.{}{}",
+            file_name_part(schema),
+            file_name_part(table),
+            format.extension(),
+            if compress { ".gz" } else { "" }
+        ));
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let sink = if compress {
+            Sink::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            Sink::Plain(file)
+        };
+
+        let mut writer = Self {
+            format,
+            columns,
+            path,
+            out: BufWriter::new(sink),
+        };
+        if format == ExportFormat::Csv {
+            let header: Vec<String> = writer
+                .columns
+                .iter()
+                .map(|c| csv_field(Some(&c.name)))
+                .collect();
+            writer.write_line(&header.join(","))?;
+        }
+        Ok(writer)
+    }
+
+    /// Append a row of rendered values (`None` for NULL), in column order
+    pub fn write_row(&mut self, values: Vec<Option<String>>) -> Result<(), MigrateError> {
+        let line = match self.format {
+            ExportFormat::Csv => {
+                let fields: Vec<String> = values.iter().map(|v| csv_field(v.as_deref())).collect();
+                fields.join(",")
+            }
+            ExportFormat::Ndjson => {
+                let object: serde_json::Map<String, serde_json::Value> = self
+                    .columns
+                    .iter()
+                    .zip(values)
+                    .map(|(col, value)| (col.name.clone(), json_value(col, value)))
+                    .collect();
+                serde_json::Value::Object(object).to_string()
+            }
+            ExportFormat::Copy => {
+                let fields: Vec<String> = values.iter().map(|v| copy_field(v.as_deref())).collect();
+                fields.join("\t")
+            }
+        };
+        self.write_line(&line)
+    }
+
+    /// Flush the file (ending the gzip stream), returning its path
+    pub fn finish(self) -> Result<PathBuf, MigrateError> {
+        let path = self.path;
+        let write_error = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+        match self
+            .out
+            .into_inner()
+            .map_err(|e| write_error(e.into_error()))?
+        {
+            Sink::Plain(_) => {}
+            Sink::Gzip(encoder) => {
+                encoder.finish().map_err(write_error)?;
+            }
+        }
+        Ok(path)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), MigrateError> {
+        writeln!(self.out, "{}", line).map_err(|e| {
+            MigrateError::Other(format!("Failed to write {}: {}", self.path.display(), e))
+        })
+    }
+}
+
+/// Name usable in a file name on every platform
+fn file_name_part(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break, or is empty
+fn csv_field(value: Option<&str>) -> String {
+    match value {
+        None => String::new(),
+        Some(v) if v.is_empty() || v.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", v.replace('"', "\"\""))
+        }
+        Some(v) => v.to_string(),
+    }
+}
+
+/// Escape a field for the `COPY` text format. `\N` is NULL, so a literal backslash has to be
+/// doubled, and tabs and line breaks would end the field or row early.
+fn copy_field(value: Option<&str>) -> String {
+    let Some(value) = value else {
+        return "\\N".to_string();
+    };
+    let mut field = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => field.push_str("\\\\"),
+            '\t' => field.push_str("\\t"),
+            '\n' => field.push_str("\\n"),
+            '\r' => field.push_str("\\r"),
+            c => field.push(c),
+        }
+    }
+    field
+}
+
+/// JSON value of a rendered column value: numbers, booleans and json columns keep their JSON
+/// type, everything else (including numeric, to keep its precision) is a string
+fn json_value(col: &ColumnInfo, value: Option<String>) -> serde_json::Value {
+    let Some(value) = value else {
+        return serde_json::Value::Null;
+    };
+    let parsed = match col.data_type.to_lowercase().as_str() {
+        "smallint" | "integer" | "bigint" => value.parse::<i64>().ok().map(serde_json::Value::from),
+        "real" | "double precision" => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number),
+        "boolean" => Some(serde_json::Value::Bool(value == "TRUE")),
+        "json" | "jsonb" => serde_json::from_str(&value).ok(),
+        _ => None,
+    };
+    parsed.unwrap_or(serde_json::Value::String(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_field_escapes_control_characters() {
+        assert_eq!(copy_field(Some("a\\b")), "a\\\\b");
+        assert_eq!(copy_field(Some("a\tb")), "a\\tb");
+        assert_eq!(copy_field(Some("a\nb")), "a\\nb");
+        assert_eq!(copy_field(Some("a\rb")), "a\\rb");
+    }
+
+    #[test]
+    fn copy_field_distinguishes_null_from_text() {
+        assert_eq!(copy_field(None), "\\N");
+        assert_eq!(copy_field(Some("")), "");
+        assert_eq!(copy_field(Some("\\N")), "\\\\N");
+    }
+
+    #[test]
+    fn csv_field_quotes_special_characters() {
+        assert_eq!(csv_field(Some("plain")), "plain");
+        assert_eq!(csv_field(Some("a\\b")), "a\\b");
+        assert_eq!(csv_field(Some("a\tb")), "a\tb");
+        assert_eq!(csv_field(Some("a,b")), "\"a,b\"");
+        assert_eq!(csv_field(Some("say \"hi\"")), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field(Some("a\nb")), "\"a\nb\"");
+        assert_eq!(csv_field(Some("a\rb")), "\"a\rb\"");
+    }
+
+    #[test]
+    fn csv_field_distinguishes_null_from_empty() {
+        assert_eq!(csv_field(None), "");
+        assert_eq!(csv_field(Some("")), "\"\"");
+        assert_eq!(csv_field(Some("\\N")), "\\N");
+    }
+}
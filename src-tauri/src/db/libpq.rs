@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::error::MigrateError;
+
+/// User's home directory, where libpq looks for `.pgpass` and `.pg_service.conf`
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Per-user libpq config file: `~/<unix_name>`, or `%APPDATA%\postgresql\<windows_name>`
+fn user_file(unix_name: &str, windows_name: &str) -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("APPDATA")
+            .map(|dir| PathBuf::from(dir).join("postgresql").join(windows_name))
+    } else {
+        home_dir().map(|dir| dir.join(unix_name))
+    }
+}
+
+/// Password for a connection from the password file (`PGPASSFILE` or `~/.pgpass`).
+/// Like libpq, the first matching line wins and `*` matches any value.
+pub fn passfile_password(host: &str, port: u16, database: &str, username: &str) -> Option<String> {
+    let path = match std::env::var_os("PGPASSFILE") {
+        Some(path) => PathBuf::from(path),
+        None => user_file(".pgpass", "pgpass.conf")?,
+    };
+    if !passfile_permissions_ok(&path) {
+        return None;
+    }
+    let contents = std::fs::read_to_string(&path).ok()?;
+    let port = port.to_string();
+    find_password(&contents, [host, port.as_str(), database, username])
+}
+
+/// Password of the first password file line matching `wanted` (host, port, database and
+/// username)
+fn find_password(contents: &str, wanted: [&str; 4]) -> Option<String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .map(split_passfile_line)
+        .find(|fields| {
+            fields.len() == 5
+                && fields[..4]
+                    .iter()
+                    .zip(wanted.iter())
+                    .all(|(field, value)| field == "*" || field == value)
+        })
+        .map(|mut fields| fields.remove(4))
+}
+
+/// libpq ignores a password file that is readable by group or others
+#[cfg(unix)]
+fn passfile_permissions_ok(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o077 == 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn passfile_permissions_ok(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Split a `host:port:database:username:password` line; `\` escapes `:` and `\`
+fn split_passfile_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            // The password is the rest of the line, colons included
+            ':' if fields.len() < 5 => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Parameters of a service from the connection service file: `PGSERVICEFILE` (or
+/// `~/.pg_service.conf`) first, then `pg_service.conf` in `PGSYSCONFDIR`
+pub fn service_parameters(service: &str) -> Result<HashMap<String, String>, MigrateError> {
+    let user_path = match std::env::var_os("PGSERVICEFILE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => user_file(".pg_service.conf", ".pg_service.conf"),
+    };
+    let system_path =
+        std::env::var_os("PGSYSCONFDIR").map(|dir| PathBuf::from(dir).join("pg_service.conf"));
+
+    for path in [user_path, system_path].into_iter().flatten() {
+        if !path.is_file() {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            MigrateError::Other(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        if let Some(parameters) = parse_service(&contents, service) {
+            return Ok(parameters);
+        }
+    }
+
+    Err(MigrateError::Other(format!(
+        "Service '{}' not found in the connection service file",
+        service
+    )))
+}
+
+/// Key/value pairs of the `[service]` section of a service file, if present
+fn parse_service(contents: &str, service: &str) -> Option<HashMap<String, String>> {
+    let mut parameters: Option<HashMap<String, String>> = None;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if parameters.is_some() {
+                break;
+            }
+            if section.trim() == service {
+                parameters = Some(HashMap::new());
+            }
+        } else if let Some(ref mut parameters) = parameters {
+            if let Some((key, value)) = line.split_once('=') {
+                parameters.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+    parameters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passfile_lines_unescape_colons_and_backslashes() {
+        assert_eq!(
+            split_passfile_line(r"db\:1:5432:app:user:p\\w:rd"),
+            ["db:1", "5432", "app", "user", "p\\w:rd"]
+        );
+    }
+
+    #[test]
+    fn passfile_matches_wildcards_and_skips_comments() {
+        let contents = "\
+# comment:*:*:*:nope
+
+other:5432:*:app:wrong
+*:5432:*:app:right
+*:*:*:*:fallback
+";
+        assert_eq!(
+            find_password(contents, ["db.example.com", "5432", "sales", "app"]).as_deref(),
+            Some("right")
+        );
+        assert_eq!(
+            find_password(contents, ["db.example.com", "6543", "sales", "app"]).as_deref(),
+            Some("fallback")
+        );
+        // A commented-out line isn't read, even when its fields would match
+        assert_eq!(
+            find_password(
+                "#db:5432:sales:app:old\n\n",
+                ["#db", "5432", "sales", "app"]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn service_section_ends_at_the_next_section() {
+        let contents = "\
+# shared services
+[staging]
+host = staging.example.com
+port=6543
+
+[prod]
+host=prod.example.com
+";
+        let staging = parse_service(contents, "staging").unwrap();
+        assert_eq!(staging.len(), 2);
+        assert_eq!(staging["host"], "staging.example.com");
+        assert_eq!(staging["port"], "6543");
+        assert_eq!(
+            parse_service(contents, "prod").unwrap()["host"],
+            "prod.example.com"
+        );
+        assert!(parse_service(contents, "dev").is_none());
+    }
+}
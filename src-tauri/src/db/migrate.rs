@@ -1,19 +1,192 @@
+use chrono::Timelike;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::postgres::PgRow;
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
+use tokio::task::JoinSet;
 
-use super::schema::{get_row_count, get_table_schema};
+use super::audit::{AuditEvent, AuditLog};
+use super::checkpoint::CheckpointFile;
+use super::connection::server_version_num;
+use super::error::MigrateError;
+use super::export::{ExportFormat, ExportWriter};
+use super::schema::{
+    generate_column_definition, generate_comment_statements, generate_create_composite_statement,
+    generate_create_enum_statement, generate_create_extension_statement,
+    generate_create_index_statement, generate_create_partition_statement,
+    generate_create_sequence_statement, generate_create_table_statement, get_all_dependencies,
+    get_comments, get_owned_sequences, get_query_schema, get_relation_kind, get_row_count,
+    get_standalone_sequences, get_table_composites, get_table_enums, get_table_extensions,
+    get_table_indexes, get_table_schema, get_table_size_estimate, quote_ident, quote_literal,
+    retarget_sequence_default, table_has_rows, validate_filter, ColumnInfo, IndexInfo,
+    PartitionInfo, RelationKind, TableSchema,
+};
+
+/// How INSERTs handle rows that conflict with existing target rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictMode {
+    /// `ON CONFLICT DO NOTHING` - keep the existing target row
+    #[default]
+    DoNothing,
+    /// `ON CONFLICT (pk) DO UPDATE SET ...` - overwrite non-PK columns. Requires a primary key
+    /// unless `conflict_target` is set.
+    Upsert,
+    /// No ON CONFLICT clause - fail the batch on any conflict
+    Error,
+}
+
+/// How a materialized view selected as a source is recreated on the target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatviewMode {
+    /// A regular table holding a copy of the view's rows
+    #[default]
+    Table,
+    /// A materialized view with the same definition, refreshed on the target. The tables it
+    /// reads from must already exist there.
+    MaterializedView,
+}
+
+/// Isolation level of the source snapshot transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotIsolation {
+    RepeatableRead,
+    /// `SERIALIZABLE READ ONLY DEFERRABLE`: may wait for a snapshot free of serialization
+    /// anomalies before the first table is read
+    Serializable,
+}
 
 /// Migration options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MigrationOptions {
     pub create_table_if_not_exists: bool,
     pub truncate_before_insert: bool,
+    /// `DROP TABLE IF EXISTS ... CASCADE` each target table and create it again from the
+    /// source, so its structure matches. Not done when resuming from a checkpoint.
+    #[serde(default)]
+    pub recreate_table: bool,
     pub disable_constraints: bool,
     pub batch_size: usize,
+    /// Rows per INSERT statement; defaults to `batch_size`. Lets wide tables fetch large
+    /// batches while keeping individual statements small.
+    #[serde(default)]
+    pub insert_batch_size: Option<usize>,
+    /// Fetch the next batch from the source while the current one is inserted into the target,
+    /// so both servers are busy at once
+    #[serde(default)]
+    pub pipeline: bool,
+    #[serde(default = "default_true")]
+    pub transactional: bool,
+    /// Maximum number of tables migrated concurrently (1 = sequential)
+    #[serde(default = "default_parallelism")]
+    pub parallelism: usize,
+    #[serde(default)]
+    pub conflict_mode: ConflictMode,
+    /// Target columns of the unique constraint or index to use as the ON CONFLICT target
+    /// instead of the primary key
+    #[serde(default)]
+    pub conflict_target: Option<Vec<String>>,
+    #[serde(default)]
+    pub matview_mode: MatviewMode,
+    /// Read source schemas and row counts but skip all DDL, TRUNCATE and INSERT on the target
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Create the target tables (and indexes, comments) but don't copy any rows
+    #[serde(default)]
+    pub schema_only: bool,
+    /// Recreate secondary indexes on the target after loading data
+    #[serde(default)]
+    pub include_indexes: bool,
+    /// Copy table and column comments (COMMENT ON) to the target
+    #[serde(default)]
+    pub include_comments: bool,
+    /// Copy standalone sequences (not owned by a column) in the migrated schemas
+    #[serde(default)]
+    pub migrate_sequences: bool,
+    /// Abort (and fail the table) when a target statement runs longer than this, e.g. while
+    /// waiting on a lock
+    #[serde(default)]
+    pub statement_timeout_ms: Option<u64>,
+    /// Retries for transient errors on each source fetch / target insert
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+    /// Cap on rows copied per second for each table, to limit load on a shared server
+    #[serde(default)]
+    pub max_rows_per_second: Option<u64>,
+    /// Load every table inside one target transaction, committed only if all tables succeed.
+    /// Runs sequentially, so it can't be combined with `parallelism > 1` or a checkpoint.
+    /// Disabled triggers (or `session_replication_role`) are undone by that transaction, and
+    /// tables stay locked against other sessions until it ends.
+    #[serde(default)]
+    pub atomic_all: bool,
+    /// Read every table through one source transaction at this isolation level, so all tables
+    /// come from the same consistent snapshot. That transaction is a single connection, so
+    /// tables are then migrated one at a time and `parallelism` is ignored.
+    #[serde(default)]
+    pub source_snapshot: Option<SnapshotIsolation>,
+    /// Allow `truncate_before_insert` when its CASCADE would also empty target tables that
+    /// aren't part of the migration, and `recreate_table` when its CASCADE would drop views or
+    /// foreign keys of other tables
+    #[serde(default)]
+    pub confirm_destructive: bool,
+    /// When an INSERT fails, split it in halves down to single rows to load every row that can
+    /// be loaded and report the primary keys of the rejected ones. Slower on failing batches.
+    #[serde(default)]
+    pub isolate_failures: bool,
+    /// When the target table already exists, add the source columns it lacks with
+    /// `ALTER TABLE ... ADD COLUMN` before loading. Columns whose type differs abort the table.
+    #[serde(default)]
+    pub add_missing_columns: bool,
+    /// Emit every statement run for a table as a `migration-log` event
+    #[serde(default)]
+    pub verbose: bool,
+    /// Append the start, each table's result and the outcome to this JSONL file as they happen,
+    /// as a record that outlives a crash of the app
+    #[serde(default)]
+    pub audit_log_path: Option<String>,
+    /// Create the sequences behind SERIAL columns under their source names and keep the
+    /// `nextval(...)` defaults, instead of letting SERIAL create new ones
+    #[serde(default)]
+    pub preserve_sequences: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_parallelism() -> usize {
+    1
+}
+
+fn default_max_retries() -> usize {
+    3
+}
+
+/// Delay before the first retry; doubled on each subsequent attempt
+const RETRY_BASE_DELAY_MS: u64 = 500;
+
+/// Rejected rows reported individually per table with `isolate_failures`; the rest are counted
+const MAX_REPORTED_ROW_FAILURES: usize = 100;
+
+/// Longest single sleep while throttling or paused, so cancellation stays responsive
+const THROTTLE_POLL_MS: u64 = 200;
+
+/// Least time between two batch `migration-progress` events of a table
+const PROGRESS_INTERVAL_MS: u64 = 100;
+
+/// Size of the row values in one INSERT above which the rows are split into more statements;
+/// well below the server's 1 GB message limit, and small enough to parse quickly
+const MAX_INSERT_BYTES: usize = 8 * 1024 * 1024;
+
+impl MigrationOptions {
+    /// Whether target tables are created from the source
+    pub fn creates_tables(&self) -> bool {
+        self.create_table_if_not_exists || self.recreate_table
+    }
 }
 
 impl Default for MigrationOptions {
@@ -21,10 +194,234 @@ impl Default for MigrationOptions {
         Self {
             create_table_if_not_exists: true,
             truncate_before_insert: false,
+            recreate_table: false,
             disable_constraints: true,
             batch_size: 1000,
+            insert_batch_size: None,
+            pipeline: false,
+            transactional: true,
+            parallelism: 1,
+            conflict_mode: ConflictMode::DoNothing,
+            conflict_target: None,
+            matview_mode: MatviewMode::Table,
+            dry_run: false,
+            schema_only: false,
+            include_indexes: false,
+            include_comments: false,
+            migrate_sequences: false,
+            statement_timeout_ms: None,
+            max_retries: 3,
+            max_rows_per_second: None,
+            atomic_all: false,
+            source_snapshot: None,
+            confirm_destructive: false,
+            isolate_failures: false,
+            add_missing_columns: false,
+            verbose: false,
+            audit_log_path: None,
+            preserve_sequences: false,
+        }
+    }
+}
+
+/// A table selected for migration, with optional per-table settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableSelection {
+    pub schema: String,
+    pub name: String,
+    /// SQL condition applied to the source rows, e.g. `created_at > now() - interval '30 days'`
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Source columns left out of the INSERT (they must be nullable or have a default)
+    #[serde(default)]
+    pub exclude_columns: Vec<String>,
+    /// Also leave `exclude_columns` out of the generated CREATE TABLE
+    #[serde(default)]
+    pub exclude_columns_from_ddl: bool,
+    /// Source column name -> target column name, for targets with renamed columns
+    #[serde(default)]
+    pub column_map: HashMap<String, String>,
+    /// Indexed source column to paginate by instead of the first primary key column. It
+    /// should be unique and NOT NULL, otherwise rows can be skipped.
+    #[serde(default)]
+    pub pagination_column: Option<String>,
+    /// Name of the table on the target, e.g. `users_backup` for `users`; defaults to `name`
+    #[serde(default)]
+    pub target_name: Option<String>,
+    /// Source column name -> transform applied to its values before they are written, e.g. to
+    /// mask personal data in a staging copy
+    #[serde(default)]
+    pub transforms: HashMap<String, ColumnTransform>,
+    /// Read the result of this query instead of the table `name`, which then only labels the
+    /// table in progress events and the checkpoint
+    #[serde(default)]
+    pub source_query: Option<SourceQuery>,
+}
+
+/// A SELECT migrated like a table, e.g. a JOIN or an aggregation. `pagination_column` names
+/// the result column to page by (it should be unique); without one the result is read through
+/// a cursor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceQuery {
+    pub sql: String,
+    /// Table created on the target from the result's column types
+    pub target_table: String,
+}
+
+/// Target schema of each source schema's tables
+#[derive(Debug, Clone, Default)]
+pub struct SchemaMap {
+    /// Target schema of every table not in `schemas`, e.g. from `target_schema_override`
+    pub all: Option<String>,
+    /// Source schema -> target schema, e.g. `sales` -> `sales_archive`
+    pub schemas: HashMap<String, String>,
+}
+
+impl SchemaMap {
+    pub fn new(all: Option<String>, schemas: HashMap<String, String>) -> Self {
+        Self { all, schemas }
+    }
+
+    /// Schema the tables of `source_schema` go to; unmapped schemas are kept as they are
+    pub fn target_schema<'a>(&'a self, source_schema: &'a str) -> &'a str {
+        self.schemas
+            .get(source_schema)
+            .or(self.all.as_ref())
+            .map(String::as_str)
+            .unwrap_or(source_schema)
+    }
+}
+
+/// Replacement for a column's values. NULLs stay NULL except with `Null`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnTransform {
+    /// Hex SHA-256 of the value's text; equal values stay equal, so joins on it still match
+    Hash,
+    /// Every value becomes NULL
+    Null,
+    /// Every value becomes this text
+    Constant(String),
+    /// Replace every match of `pattern` (`regex` crate syntax) with `replacement`, where `$1`
+    /// refers to the first capture group
+    RegexpReplace {
+        pattern: String,
+        replacement: String,
+    },
+}
+
+impl ColumnTransform {
+    /// Prepare the transform, compiling its pattern
+    fn compile(&self) -> Result<CompiledTransform, MigrateError> {
+        Ok(match self {
+            ColumnTransform::Hash => CompiledTransform::Hash,
+            ColumnTransform::Null => CompiledTransform::Null,
+            ColumnTransform::Constant(value) => CompiledTransform::Constant(value.clone()),
+            ColumnTransform::RegexpReplace {
+                pattern,
+                replacement,
+            } => CompiledTransform::RegexpReplace(
+                regex::Regex::new(pattern)
+                    .map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?,
+                replacement.clone(),
+            ),
+        })
+    }
+}
+
+/// A `ColumnTransform` ready to apply
+enum CompiledTransform {
+    Hash,
+    Null,
+    Constant(String),
+    RegexpReplace(regex::Regex, String),
+}
+
+impl CompiledTransform {
+    /// Transform a serialized value, returning the literal to write instead
+    fn apply(&self, literal: String) -> String {
+        let Some(text) = literal_to_text(literal) else {
+            return "NULL".to_string();
+        };
+        match self {
+            CompiledTransform::Hash => {
+                let digest = Sha256::digest(text.as_bytes());
+                let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+                quote_literal(&hex)
+            }
+            CompiledTransform::Null => "NULL".to_string(),
+            CompiledTransform::Constant(value) => quote_literal(value),
+            CompiledTransform::RegexpReplace(pattern, replacement) => {
+                quote_literal(&pattern.replace_all(&text, replacement.as_str()))
+            }
+        }
+    }
+}
+
+/// The selection's transforms of `columns`, by position. Every transformed column has to
+/// exist in `table_columns`.
+fn compile_transforms(
+    selection: &TableSelection,
+    table_columns: &[ColumnInfo],
+    columns: &[ColumnInfo],
+) -> Result<Vec<Option<CompiledTransform>>, MigrateError> {
+    if let Some(missing) = selection
+        .transforms
+        .keys()
+        .find(|name| !table_columns.iter().any(|c| &c.name == *name))
+    {
+        return Err(MigrateError::Other(format!(
+            "Transformed column {} does not exist",
+            missing
+        )));
+    }
+    columns
+        .iter()
+        .map(|col| {
+            selection
+                .transforms
+                .get(&col.name)
+                .map(ColumnTransform::compile)
+                .transpose()
+        })
+        .collect()
+}
+
+impl TableSelection {
+    /// The row filter, ignoring blank strings
+    pub fn row_filter(&self) -> Option<&str> {
+        self.filter
+            .as_deref()
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+    }
+
+    /// Name of the table on the target, after `target_name` or the source query's table
+    pub fn target_table(&self) -> &str {
+        if let Some(ref query) = self.source_query {
+            return &query.target_table;
+        }
+        self.target_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .unwrap_or(&self.name)
+    }
+
+    /// What the rows are read from: the quoted table, or the source query as a subquery
+    fn source_relation(&self) -> String {
+        match self.source_query {
+            Some(ref query) => format!("({}) AS source_query", query.sql),
+            None => format!("{}.{}", quote_ident(&self.schema), quote_ident(&self.name)),
         }
     }
+
+    /// Name of a source column on the target, after `column_map`
+    pub fn target_column<'a>(&'a self, source_column: &'a str) -> &'a str {
+        self.column_map
+            .get(source_column)
+            .map(String::as_str)
+            .unwrap_or(source_column)
+    }
 }
 
 /// Migration progress event
@@ -37,6 +434,48 @@ pub struct MigrationProgress {
     pub total_rows: i64,
     pub status: String,
     pub error: Option<String>,
+    pub rows_per_second: f64,
+    pub eta_seconds: Option<u64>,
+}
+
+/// Overall migration progress event, emitted after each table finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverallProgress {
+    pub tables_done: usize,
+    pub total_tables: usize,
+    pub rows_transferred: i64,
+    pub elapsed_ms: u64,
+}
+
+/// Emitted before the migration starts when TRUNCATE ... CASCADE reaches unselected tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationWarning {
+    pub message: String,
+    pub tables: Vec<String>, // "schema.table"
+}
+
+/// Emitted with `verbose` for each statement run for a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationLog {
+    pub table: String,    // "schema.table"
+    pub database: String, // "source" or "target"
+    /// Cut to MAX_LOGGED_STATEMENT_CHARS; INSERTs leave out their values
+    pub statement: String,
+    /// Rows fetched or sent by the statement
+    pub rows: Option<usize>,
+}
+
+/// Longest statement text sent in a `migration-log` event
+const MAX_LOGGED_STATEMENT_CHARS: usize = 2000;
+
+/// Outcome of a single table's migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableResult {
+    pub schema: String,
+    pub table: String,
+    pub rows: i64,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
 }
 
 /// Migration result
@@ -47,326 +486,4017 @@ pub struct MigrationResult {
     pub total_rows: i64,
     pub errors: Vec<String>,
     pub elapsed_ms: u64,
+    pub table_results: Vec<TableResult>,
+    pub migration_id: String, // filled in by the start_migration command
+}
+
+/// Source vs target comparison of one migrated table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyResult {
+    pub schema: String,
+    pub table: String,
+    pub source_count: i64,
+    pub target_count: i64,
+    pub source_checksum: Option<String>, // only when checksums were requested and the table has a PK
+    pub target_checksum: Option<String>,
+    pub matched: bool,
+    pub error: Option<String>,
+}
+
+/// A column whose type the migration can't read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsupportedColumn {
+    pub column: String,
+    pub data_type: String,
+}
+
+/// Pre-flight estimate for one selected table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableEstimate {
+    pub schema: String,
+    pub table: String,
+    pub estimated_rows: i64, // from planner statistics, ignoring any row filter
+    pub size_bytes: i64,     // including indexes and TOAST
+    pub unsupported_columns: Vec<UnsupportedColumn>,
+    pub error: Option<String>,
+}
+
+/// Pre-flight estimate of a whole migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationEstimate {
+    pub total_rows: i64,
+    pub total_bytes: i64,
+    pub tables_with_unsupported_columns: usize,
+    pub tables: Vec<TableEstimate>,
+}
+
+/// Whether the target role can write a selected table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableReadiness {
+    pub schema: String, // target schema
+    pub table: String,
+    pub exists: bool,
+    pub problems: Vec<String>,
+}
+
+/// Target-side pre-flight report. PostgreSQL doesn't expose free disk space over SQL, so the
+/// current database size is reported instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetReadiness {
+    pub ready: bool, // no problems found
+    pub in_recovery: bool,
+    pub read_only: bool,
+    pub database_size_bytes: i64,
+    pub problems: Vec<String>,
+    pub tables: Vec<TableReadiness>,
+}
+
+/// Distinct values of a column, rendered as text, to help write a filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistinctValues {
+    pub values: Vec<Option<String>>, // sorted, None for SQL NULL
+    /// The column has more distinct values than were returned
+    pub truncated: bool,
+}
+
+/// First rows of a table, rendered as text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TablePreview {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>, // None for SQL NULL
 }
 
 /// Cancellation token for migrations
 pub type CancellationToken = Arc<AtomicBool>;
 
+/// Set while a migration is paused; tables wait between batches until it's cleared
+pub type PauseToken = Arc<AtomicBool>;
+
+/// Target transaction shared by every table when `atomic_all` is set
+type SharedTransaction = tokio::sync::Mutex<sqlx::Transaction<'static, sqlx::Postgres>>;
+
+/// Source transaction every table is read through when `source_snapshot` is set
+struct SourceSnapshot {
+    tx: sqlx::Transaction<'static, sqlx::Postgres>,
+    reading: bool, // a table's savepoint is open
+}
+
+type SharedSnapshot = tokio::sync::Mutex<SourceSnapshot>;
+
+/// Open the read-only source transaction whose snapshot all tables are read from
+async fn begin_snapshot(
+    pool: &PgPool,
+    isolation: SnapshotIsolation,
+) -> Result<SharedSnapshot, MigrateError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| MigrateError::sql("Failed to begin source snapshot", e))?;
+    let level = match isolation {
+        SnapshotIsolation::RepeatableRead => "REPEATABLE READ, READ ONLY",
+        SnapshotIsolation::Serializable => "SERIALIZABLE, READ ONLY, DEFERRABLE",
+    };
+    sqlx::query(&format!("SET TRANSACTION ISOLATION LEVEL {}", level))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to begin source snapshot", e))?;
+
+    Ok(SharedSnapshot::new(SourceSnapshot { tx, reading: false }))
+}
+
+/// End the source snapshot; it only read, so rolling back is enough
+async fn end_snapshot(snapshot: Option<Arc<SharedSnapshot>>) {
+    if let Some(snapshot) = snapshot.and_then(|s| Arc::try_unwrap(s).ok()) {
+        let _ = snapshot.into_inner().tx.rollback().await;
+    }
+}
+
 pub fn create_cancellation_token() -> CancellationToken {
     Arc::new(AtomicBool::new(false))
 }
 
+pub fn create_pause_token() -> PauseToken {
+    Arc::new(AtomicBool::new(false))
+}
+
 /// Migrate tables from source to target
+#[allow(clippy::too_many_arguments)]
 pub async fn migrate_tables(
     app_handle: AppHandle,
     source_pool: &PgPool,
     target_pool: &PgPool,
-    tables: Vec<(String, String)>, // (schema, table)
+    tables: Vec<TableSelection>,
     options: MigrationOptions,
     cancel_token: CancellationToken,
-    target_schema_override: Option<String>,
+    pause_token: PauseToken,
+    schema_map: SchemaMap,
+    checkpoint_path: Option<String>,
 ) -> MigrationResult {
     let start = std::time::Instant::now();
     let mut tables_migrated = 0;
     let mut total_rows: i64 = 0;
     let mut errors = Vec::new();
-    let total_tables = tables.len();
-
-    for (idx, (schema, table)) in tables.iter().enumerate() {
-        if cancel_token.load(Ordering::Relaxed) {
-            errors.push("Migration cancelled by user".to_string());
-            break;
+    let mut table_results: Vec<TableResult> = Vec::new();
+    // Open the checkpoint so tables completed by a previous run can be skipped
+    let checkpoint = match checkpoint_path.as_deref().map(CheckpointFile::open) {
+        Some(Ok(checkpoint)) => Some(Arc::new(checkpoint)),
+        Some(Err(e)) => {
+            errors.push(e);
+            None
+        }
+        None => None,
+    };
+    let audit_log = match options.audit_log_path.as_deref().map(AuditLog::open) {
+        Some(Ok(audit_log)) => Some(audit_log),
+        Some(Err(e)) => {
+            errors.push(e);
+            None
         }
+        None => None,
+    };
 
-        let progress = MigrationProgress {
-            table_name: table.clone(),
-            current_table: idx + 1,
-            total_tables,
-            rows_transferred: 0,
-            total_rows: 0,
-            status: "Starting".to_string(),
-            error: None,
-        };
-        let _ = app_handle.emit("migration-progress", &progress);
+    if options.atomic_all {
+        if options.parallelism > 1 {
+            errors.push(
+                "atomic_all runs every table on one connection and can't be combined with parallelism > 1"
+                    .to_string(),
+            );
+        }
+        if checkpoint_path.is_some() {
+            errors.push(
+                "atomic_all can't be combined with a checkpoint, nothing is kept until the final commit"
+                    .to_string(),
+            );
+        }
+    }
 
-        match migrate_single_table(
-            &app_handle,
-            source_pool,
-            target_pool,
-            schema,
-            table,
-            &options,
-            &cancel_token,
-            idx + 1,
-            total_tables,
-            target_schema_override.as_deref(),
-        )
-        .await
-        {
-            Ok(rows) => {
-                tables_migrated += 1;
-                total_rows += rows;
+    // Validate relation kinds, transforms, row filters, column maps and the conflict target up
+    // front so a typo fails fast rather than mid-migration
+    for selection in &tables {
+        // A source query has no table to check these against; it must at least describe
+        if let Some(ref query) = selection.source_query {
+            let described = get_query_schema(
+                source_pool,
+                &query.sql,
+                &selection.schema,
+                &query.target_table,
+            )
+            .await;
+            let key = selection.pagination_column.as_deref().map(str::trim);
+            match described {
+                Ok(described) => {
+                    if let Some(key) = key.filter(|k| !k.is_empty()) {
+                        if !described.columns.iter().any(|c| c.name == key) {
+                            errors.push(format!(
+                                "{}: pagination column {} is not a column of the source query",
+                                selection.name, key
+                            ));
+                        }
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", selection.name, e)),
             }
-            Err(e) => {
-                errors.push(format!("{}.{}: {}", schema, table, e));
+            continue;
+        }
+        // A foreign table's rows live on another server; reading it here would go through the
+        // FDW and can hang on an unreachable remote
+        match get_relation_kind(source_pool, &selection.schema, &selection.name).await {
+            Ok(Some(RelationKind::ForeignTable)) => errors.push(format!(
+                "{}.{} is a foreign table, migrate it from its remote server instead",
+                selection.schema, selection.name
+            )),
+            Ok(_) => {}
+            Err(e) => errors.push(format!("{}.{}: {}", selection.schema, selection.name, e)),
+        }
+        for transform in selection.transforms.values() {
+            if let Err(e) = transform.compile() {
+                errors.push(format!("{}.{}: {}", selection.schema, selection.name, e));
+            }
+        }
+        if let Some(filter) = selection.row_filter() {
+            if let Err(e) =
+                validate_filter(source_pool, &selection.schema, &selection.name, filter).await
+            {
+                errors.push(format!("{}.{}: {}", selection.schema, selection.name, e));
+            }
+        }
+        if !selection.column_map.is_empty() {
+            let target_schema = schema_map.target_schema(&selection.schema);
+            if let Err(e) =
+                validate_column_map(source_pool, target_pool, selection, target_schema).await
+            {
+                errors.push(format!("{}.{}: {}", selection.schema, selection.name, e));
+            }
+        }
+        if let Some(ref columns) = options.conflict_target {
+            if options.conflict_mode != ConflictMode::Error {
+                let target_schema = schema_map.target_schema(&selection.schema);
+                if let Err(e) = validate_conflict_target(
+                    source_pool,
+                    target_pool,
+                    selection,
+                    target_schema,
+                    columns,
+                )
+                .await
+                {
+                    errors.push(format!("{}.{}: {}", selection.schema, selection.name, e));
+                }
             }
         }
     }
 
-    let elapsed = start.elapsed().as_millis() as u64;
-
-    MigrationResult {
-        success: errors.is_empty(),
-        tables_migrated,
-        total_rows,
-        errors,
-        elapsed_ms: elapsed,
+    // TRUNCATE ... CASCADE also empties target tables referencing the truncated ones
+    if options.truncate_before_insert && !options.dry_run && !options.schema_only {
+        match truncate_cascade_tables(target_pool, &tables, &schema_map).await {
+            Ok(affected) if !affected.is_empty() => {
+                let affected: Vec<String> = affected
+                    .into_iter()
+                    .map(|(schema, table)| format!("{}.{}", schema, table))
+                    .collect();
+                let message = format!(
+                    "Truncating with CASCADE would also empty tables outside the selection: {}",
+                    affected.join(", ")
+                );
+                let _ = app_handle.emit(
+                    "migration-warning",
+                    &MigrationWarning {
+                        message: message.clone(),
+                        tables: affected,
+                    },
+                );
+                if !options.confirm_destructive {
+                    errors.push(format!("{}. Set confirm_destructive to proceed.", message));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => errors.push(e.to_string()),
+        }
     }
-}
-
-/// Migrate a single table
-async fn migrate_single_table(
-    app_handle: &AppHandle,
-    source_pool: &PgPool,
-    target_pool: &PgPool,
-    schema: &str,
-    table: &str,
-    options: &MigrationOptions,
-    cancel_token: &CancellationToken,
-    current_table: usize,
-    total_tables: usize,
-    target_schema_override: Option<&str>,
-) -> Result<i64, String> {
-    let target_schema = target_schema_override.unwrap_or(schema);
-    let source_full_table = format!("\"{}\".\"{}\"", schema, table);
-    let target_full_table = format!("\"{}\".\"{}\"", target_schema, table);
 
-    // Get source table schema and row count
-    let table_schema = get_table_schema(source_pool, schema, table).await?;
-    let total_rows = get_row_count(source_pool, schema, table).await?;
+    // DROP TABLE ... CASCADE also drops views over the recreated tables and foreign keys
+    // referencing them
+    if options.recreate_table && !options.dry_run {
+        match drop_cascade_objects(target_pool, &tables, &schema_map).await {
+            Ok(dropped) if !dropped.is_empty() => {
+                let mut relations: Vec<String> = dropped.iter().map(|(r, _)| r.clone()).collect();
+                relations.dedup();
+                let objects: Vec<String> = dropped.into_iter().map(|(_, o)| o).collect();
+                let message = format!(
+                    "Recreating with CASCADE would also drop: {}",
+                    objects.join(", ")
+                );
+                let _ = app_handle.emit(
+                    "migration-warning",
+                    &MigrationWarning {
+                        message: message.clone(),
+                        tables: relations,
+                    },
+                );
+                if !options.confirm_destructive {
+                    errors.push(format!("{}. Set confirm_destructive to proceed.", message));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
 
-    // Emit initial progress
-    let progress = MigrationProgress {
-        table_name: table.to_string(),
-        current_table,
-        total_tables,
-        rows_transferred: 0,
-        total_rows,
-        status: "Preparing".to_string(),
-        error: None,
+    // With atomic_all every table is loaded over one connection inside a single transaction
+    let shared_tx = if options.atomic_all && !options.dry_run && errors.is_empty() {
+        match target_pool.begin().await {
+            Ok(tx) => Some(Arc::new(SharedTransaction::new(tx))),
+            Err(e) => {
+                errors.push(format!("Failed to begin transaction: {}", e));
+                None
+            }
+        }
+    } else {
+        None
     };
-    let _ = app_handle.emit("migration-progress", &progress);
-
-    // Ensure target schema exists
-    let schema_query = format!("CREATE SCHEMA IF NOT EXISTS \"{}\"", target_schema);
-    let _ = sqlx::query(&schema_query)
-        .execute(target_pool)
-        .await;
-
-    // Create table if needed
-    if options.create_table_if_not_exists {
-        // Modify create statement to handle schema change and IF NOT EXISTS
-        let create_stmt = table_schema.create_statement
-            .replace(
-                &format!("CREATE TABLE \"{}\".\"{}\"", schema, table),
-                &format!("CREATE TABLE IF NOT EXISTS \"{}\".\"{}\"", target_schema, table)
-            );
-        
-        sqlx::query(&create_stmt)
-            .execute(target_pool)
-            .await
-            .map_err(|e| format!("Failed to create table: {}", e))?;
+    let source_snapshot = match options.source_snapshot {
+        Some(isolation) if !options.dry_run && errors.is_empty() => {
+            match begin_snapshot(source_pool, isolation).await {
+                Ok(snapshot) => Some(Arc::new(snapshot)),
+                Err(e) => {
+                    errors.push(e.to_string());
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    if !errors.is_empty() {
+        return MigrationResult {
+            success: false,
+            tables_migrated,
+            total_rows,
+            errors,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+            table_results,
+            migration_id: String::new(),
+        };
     }
 
-    // Truncate if needed
-    if options.truncate_before_insert {
-        sqlx::query(&format!("TRUNCATE TABLE {} CASCADE", target_full_table))
-            .execute(target_pool)
-            .await
-            .map_err(|e| format!("Failed to truncate: {}", e))?;
+    // Keyed by target table, since one source table can be selected into several targets
+    let tables: Vec<TableSelection> = match checkpoint {
+        Some(ref checkpoint) => tables
+            .into_iter()
+            .filter(|t| {
+                !checkpoint.is_completed(schema_map.target_schema(&t.schema), t.target_table())
+            })
+            .collect(),
+        None => tables,
+    };
+    let total_tables = tables.len();
+    record_audit(
+        audit_log.as_ref(),
+        &AuditEvent::Started {
+            tables: total_tables,
+            dry_run: options.dry_run,
+        },
+        &mut errors,
+    );
+    let mut migrated_schemas: Vec<String> = Vec::new();
+    for selection in &tables {
+        if !migrated_schemas.contains(&selection.schema) {
+            migrated_schemas.push(selection.schema.clone());
+        }
     }
+    // The snapshot is a single source connection, so tables are read one at a time
+    let mut parallelism = if source_snapshot.is_some() {
+        1
+    } else {
+        options.parallelism.max(1)
+    };
 
-    // Disable constraints if needed
-    if options.disable_constraints {
-        let _ = sqlx::query(&format!("ALTER TABLE {} DISABLE TRIGGER ALL", target_full_table))
-            .execute(target_pool)
-            .await;
+    // When running in parallel, parents must finish before their dependents start
+    let mut dependencies: HashMap<(String, String), Vec<(String, String)>> = HashMap::new();
+    if parallelism > 1 {
+        match selected_dependencies(source_pool, &tables).await {
+            Ok(deps) => dependencies = deps,
+            Err(e) => {
+                errors.push(format!("{} (falling back to sequential migration)", e));
+                parallelism = 1;
+            }
+        }
     }
 
-    // Build column list
-    let columns: Vec<String> = table_schema
-        .columns
-        .iter()
-        .map(|c| format!("\"{}\"", c.name))
-        .collect();
-    let column_list = columns.join(", ");
-
-    // Stream data in batches
-    let mut rows_transferred: i64 = 0;
-    let batch_size = options.batch_size as i64;
-    
-    // For Keyset Pagination (much faster than OFFSET)
-    let pk_col = table_schema.primary_key_columns.first().cloned();
-    let mut last_pk_value: Option<String> = None;
+    // A parent is finished once every selection of it is, as it may be copied to several targets
+    let mut unfinished: HashMap<(String, String), usize> = HashMap::new();
+    for selection in &tables {
+        *unfinished
+            .entry((selection.schema.clone(), selection.name.clone()))
+            .or_default() += 1;
+    }
+    let mut pending: Vec<(usize, TableSelection)> = tables.into_iter().enumerate().collect();
+    let mut join_set = JoinSet::new();
+    let mut cancelled = false;
+    let mut failed = false;
+    let mut tables_done = 0;
 
-    loop {
-        if cancel_token.load(Ordering::Relaxed) {
-            return Err("Migration cancelled".to_string());
+    while !pending.is_empty() || !join_set.is_empty() {
+        if !cancelled && cancel_token.load(Ordering::Relaxed) {
+            cancelled = true;
+            errors.push("Migration cancelled by user".to_string());
+            pending.clear();
         }
 
-        // Build Fetch Query with Keyset Pagination if possible (on SOURCE)
-        let select_query = if let Some(ref pk) = pk_col {
-            let where_clause = if let Some(ref last_val) = last_pk_value {
-                format!("WHERE \"{}\" > {}", pk, last_val)
-            } else {
-                "".to_string()
+        // Start tables whose parents have finished, up to the parallelism limit
+        while join_set.len() < parallelism {
+            let ready = pending.iter().position(|(_, selection)| {
+                dependencies
+                    .get(&(selection.schema.clone(), selection.name.clone()))
+                    .map(|parents| parents.iter().all(|p| !unfinished.contains_key(p)))
+                    .unwrap_or(true)
+            });
+            let next = match ready {
+                Some(pos) => pos,
+                // Dependency cycle (or failed parent task): start the next table anyway
+                None if join_set.is_empty() && !pending.is_empty() => 0,
+                None => break,
             };
-            format!(
-                "SELECT {} FROM {} {} ORDER BY \"{}\" LIMIT {}",
-                column_list, source_full_table, where_clause, pk, batch_size
-            )
-        } else {
-            // Fallback to OFFSET if no PK
-            format!(
-                "SELECT {} FROM {} ORDER BY 1 LIMIT {} OFFSET {}",
-                column_list, source_full_table, batch_size, rows_transferred
-            )
-        };
+            let (idx, selection) = pending.remove(next);
 
-        let rows: Vec<PgRow> = sqlx::query(&select_query)
-            .fetch_all(source_pool)
-            .await
-            .map_err(|e| format!("Failed to fetch data: {}", e))?;
+            let progress = MigrationProgress {
+                table_name: selection.name.clone(),
+                current_table: idx + 1,
+                total_tables,
+                rows_transferred: 0,
+                total_rows: 0,
+                status: "Starting".to_string(),
+                error: None,
+                rows_per_second: 0.0,
+                eta_seconds: None,
+            };
+            let _ = app_handle.emit("migration-progress", &progress);
 
-        if rows.is_empty() {
-            break;
+            let app_handle = app_handle.clone();
+            let source_pool = source_pool.clone();
+            let target_pool = target_pool.clone();
+            let options = options.clone();
+            let cancel_token = cancel_token.clone();
+            let pause_token = pause_token.clone();
+            let schema_map = schema_map.clone();
+            let checkpoint = checkpoint.clone();
+            let shared_tx = shared_tx.clone();
+            let source_snapshot = source_snapshot.clone();
+
+            join_set.spawn(async move {
+                let table_start = std::time::Instant::now();
+                let result = migrate_single_table(
+                    &app_handle,
+                    &source_pool,
+                    &target_pool,
+                    &selection,
+                    &options,
+                    &cancel_token,
+                    &pause_token,
+                    idx + 1,
+                    total_tables,
+                    &schema_map,
+                    checkpoint.as_deref(),
+                    shared_tx.as_deref(),
+                    source_snapshot.as_deref(),
+                )
+                .await;
+                (selection, result, table_start.elapsed().as_millis() as u64)
+            });
         }
 
-        let batch_count = rows.len() as i64;
+        match join_set.join_next().await {
+            Some(Ok((selection, result, table_elapsed))) => {
+                match result {
+                    Ok((rows, warnings)) => {
+                        tables_migrated += 1;
+                        for warning in warnings {
+                            errors.push(format!(
+                                "{}.{}: {}",
+                                selection.schema, selection.name, warning
+                            ));
+                        }
+                        total_rows += rows;
+                        table_results.push(TableResult {
+                            schema: selection.schema.clone(),
+                            table: selection.name.clone(),
+                            rows,
+                            elapsed_ms: table_elapsed,
+                            error: None,
+                        });
 
-        // Build a single Multi-Row INSERT statement (Turbo Mode)
-        let mut row_values = Vec::new();
-        for row in &rows {
-            let values = build_insert_values(row, &table_schema.columns)?;
-            row_values.push(format!("({})", values));
-            
-            // Track last PK for next batch
-            if let Some(ref pk) = pk_col {
-                if let Ok(val) = get_column_value_as_sql(row, pk, "text") {
-                    last_pk_value = Some(val);
+                        if let Some(ref checkpoint) = checkpoint {
+                            if !options.dry_run {
+                                if let Err(e) = checkpoint.mark_completed(
+                                    schema_map.target_schema(&selection.schema),
+                                    selection.target_table(),
+                                ) {
+                                    errors.push(e);
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        failed = true;
+                        errors.push(format!("{}.{}: {}", selection.schema, selection.name, e));
+                        table_results.push(TableResult {
+                            schema: selection.schema.clone(),
+                            table: selection.name.clone(),
+                            rows: 0,
+                            elapsed_ms: table_elapsed,
+                            error: Some(e.to_string()),
+                        });
+                    }
+                }
+                // Inside the shared transaction a table's rows are only kept on commit, so its
+                // result is recorded once that's known
+                if shared_tx.is_none() {
+                    if let Some(result) = table_results.last() {
+                        let event = AuditEvent::Table(result.clone());
+                        record_audit(audit_log.as_ref(), &event, &mut errors);
+                    }
+                }
+                let key = (selection.schema, selection.name);
+                if let Some(count) = unfinished.get_mut(&key) {
+                    *count -= 1;
+                    if *count == 0 {
+                        unfinished.remove(&key);
+                    }
                 }
             }
+            Some(Err(e)) => {
+                failed = true;
+                errors.push(format!("Migration task failed: {}", e));
+            }
+            None => continue,
         }
 
-        // INSERT into TARGET
-        let insert_query = format!(
-            "INSERT INTO {} ({}) VALUES {} ON CONFLICT DO NOTHING",
-            target_full_table,
-            column_list,
-            row_values.join(", ")
-        );
+        tables_done += 1;
+        let overall = OverallProgress {
+            tables_done,
+            total_tables,
+            rows_transferred: total_rows,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        };
+        let _ = app_handle.emit("migration-overall", &overall);
 
-        sqlx::query(&insert_query)
-            .execute(target_pool)
-            .await
-            .map_err(|e| format!("Turbo Insert failed: {}", e))?;
+        // The shared transaction is doomed once a table fails, so don't start any more
+        if failed && shared_tx.is_some() {
+            pending.clear();
+        }
+    }
 
-        rows_transferred += batch_count;
+    // All or nothing: keep the loaded rows only if every table succeeded
+    if let Some(shared_tx) = shared_tx {
+        let committed = match Arc::try_unwrap(shared_tx) {
+            Ok(tx) if !failed && !cancelled => match tx.into_inner().commit().await {
+                Ok(()) => true,
+                Err(e) => {
+                    errors.push(format!("Failed to commit: {}", e));
+                    false
+                }
+            },
+            Ok(tx) => {
+                let _ = tx.into_inner().rollback().await;
+                false
+            }
+            // Every table task has finished, so nothing else holds the transaction
+            Err(_) => false,
+        };
+        if !committed {
+            errors.push("Migration rolled back, no rows were kept".to_string());
+            tables_migrated = 0;
+            total_rows = 0;
+            for result in table_results.iter_mut().filter(|r| r.error.is_none()) {
+                result.rows = 0;
+                result.error = Some("Rolled back, no rows were kept".to_string());
+            }
+        }
+        for result in &table_results {
+            let event = AuditEvent::Table(result.clone());
+            record_audit(audit_log.as_ref(), &event, &mut errors);
+        }
+    }
+
+    end_snapshot(source_snapshot).await;
+
+    // Standalone sequences aren't tied to any table, so copy them once per schema
+    if options.migrate_sequences && !options.dry_run && !cancelled {
+        for schema in &migrated_schemas {
+            let target_schema = schema_map.target_schema(schema);
+            if let Err(e) = migrate_sequences(source_pool, target_pool, schema, target_schema).await
+            {
+                errors.push(format!("{}: {}", schema, e));
+            }
+        }
+    }
+
+    // Nothing left to resume once everything succeeded
+    if let Some(ref checkpoint) = checkpoint {
+        if errors.is_empty() && !options.dry_run {
+            if let Err(e) = checkpoint.remove() {
+                errors.push(e);
+            }
+        }
+    }
+
+    let elapsed = start.elapsed().as_millis() as u64;
+    record_audit(
+        audit_log.as_ref(),
+        &AuditEvent::Finished {
+            success: errors.is_empty(),
+            tables_migrated,
+            total_rows,
+            elapsed_ms: elapsed,
+            errors: errors.clone(),
+        },
+        &mut errors,
+    );
+
+    MigrationResult {
+        success: errors.is_empty(),
+        tables_migrated,
+        total_rows,
+        errors,
+        elapsed_ms: elapsed,
+        table_results,
+        migration_id: String::new(),
+    }
+}
+
+/// Append an event to the audit log, if there is one. A failed write is reported with the
+/// migration's errors but doesn't stop it.
+fn record_audit(audit_log: Option<&AuditLog>, event: &AuditEvent, errors: &mut Vec<String>) {
+    if let Some(audit_log) = audit_log {
+        if let Err(e) = audit_log.record(event) {
+            errors.push(e);
+        }
+    }
+}
+
+/// Export tables to CSV or NDJSON files in `output_dir` instead of a target database, one
+/// file per table, gzip-compressed with `compress`. Uses the batch size, retries, throttling
+/// and source snapshot of `options`.
+#[allow(clippy::too_many_arguments)]
+pub async fn export_tables(
+    app_handle: AppHandle,
+    source_pool: &PgPool,
+    mut tables: Vec<TableSelection>,
+    options: MigrationOptions,
+    output_dir: &Path,
+    format: ExportFormat,
+    compress: bool,
+    cancel_token: CancellationToken,
+) -> MigrationResult {
+    let start = std::time::Instant::now();
+    let mut tables_migrated = 0;
+    let mut total_rows: i64 = 0;
+    let mut errors = Vec::new();
+    let mut table_results: Vec<TableResult> = Vec::new();
+    let total_tables = tables.len();
+    let source_snapshot = match options.source_snapshot {
+        Some(isolation) => match begin_snapshot(source_pool, isolation).await {
+            Ok(snapshot) => Some(Arc::new(snapshot)),
+            Err(e) => {
+                errors.push(e.to_string());
+                tables.clear();
+                None
+            }
+        },
+        None => None,
+    };
+    let audit_log = match options.audit_log_path.as_deref().map(AuditLog::open) {
+        Some(Ok(audit_log)) => Some(audit_log),
+        Some(Err(e)) => {
+            errors.push(e);
+            tables.clear();
+            None
+        }
+        None => None,
+    };
+    if !tables.is_empty() {
+        record_audit(
+            audit_log.as_ref(),
+            &AuditEvent::Started {
+                tables: total_tables,
+                dry_run: false,
+            },
+            &mut errors,
+        );
+    }
+
+    for (idx, selection) in tables.iter().enumerate() {
+        if cancel_token.load(Ordering::Relaxed) {
+            errors.push("Export cancelled by user".to_string());
+            break;
+        }
+
+        let table_start = std::time::Instant::now();
+        let result = export_single_table(
+            &app_handle,
+            source_pool,
+            selection,
+            &options,
+            output_dir,
+            format,
+            compress,
+            &cancel_token,
+            source_snapshot.as_deref(),
+            idx + 1,
+            total_tables,
+        )
+        .await;
+        let (rows, error) = match result {
+            Ok((rows, warnings)) => {
+                tables_migrated += 1;
+                total_rows += rows;
+                for warning in warnings {
+                    errors.push(format!(
+                        "{}.{}: {}",
+                        selection.schema, selection.name, warning
+                    ));
+                }
+                (rows, None)
+            }
+            Err(e) => {
+                errors.push(format!("{}.{}: {}", selection.schema, selection.name, e));
+                (0, Some(e.to_string()))
+            }
+        };
+        let result = TableResult {
+            schema: selection.schema.clone(),
+            table: selection.name.clone(),
+            rows,
+            elapsed_ms: table_start.elapsed().as_millis() as u64,
+            error,
+        };
+        record_audit(
+            audit_log.as_ref(),
+            &AuditEvent::Table(result.clone()),
+            &mut errors,
+        );
+        table_results.push(result);
+
+        let overall = OverallProgress {
+            tables_done: idx + 1,
+            total_tables,
+            rows_transferred: total_rows,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        };
+        let _ = app_handle.emit("migration-overall", &overall);
+    }
+    end_snapshot(source_snapshot).await;
+    if !table_results.is_empty() {
+        record_audit(
+            audit_log.as_ref(),
+            &AuditEvent::Finished {
+                success: errors.is_empty(),
+                tables_migrated,
+                total_rows,
+                elapsed_ms: start.elapsed().as_millis() as u64,
+                errors: errors.clone(),
+            },
+            &mut errors,
+        );
+    }
+
+    MigrationResult {
+        success: errors.is_empty(),
+        tables_migrated,
+        total_rows,
+        errors,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+        table_results,
+        migration_id: String::new(),
+    }
+}
+
+/// Write one table to its export file, returning the rows written and any warnings
+#[allow(clippy::too_many_arguments)]
+async fn export_single_table(
+    app_handle: &AppHandle,
+    source_pool: &PgPool,
+    selection: &TableSelection,
+    options: &MigrationOptions,
+    output_dir: &Path,
+    format: ExportFormat,
+    compress: bool,
+    cancel_token: &CancellationToken,
+    source_snapshot: Option<&SharedSnapshot>,
+    current_table: usize,
+    total_tables: usize,
+) -> Result<(i64, Vec<String>), MigrateError> {
+    let schema = selection.schema.as_str();
+    let table = selection.name.as_str();
+    let row_filter = selection.row_filter();
+    if let Some(filter) = row_filter {
+        validate_filter(source_pool, schema, table, filter).await?;
+    }
+
+    let table_schema = get_table_schema(source_pool, schema, table).await?;
+    let mut warnings = Vec::new();
+    let key_col = pagination_column(source_pool, selection, &table_schema, &mut warnings).await?;
+    let total_rows = get_row_count(source_pool, schema, table, row_filter).await?;
+
+    let exported: Vec<ColumnInfo> = table_schema
+        .columns
+        .iter()
+        .filter(|c| !selection.exclude_columns.contains(&c.name))
+        .cloned()
+        .collect();
+    let (columns, select_list) = rendered_columns(exported);
+    let transforms = compile_transforms(selection, &table_schema.columns, &columns)?;
+
+    // The file uses the mapped column names
+    let file_columns = columns
+        .iter()
+        .map(|c| ColumnInfo {
+            name: selection.target_column(&c.name).to_string(),
+            ..c.clone()
+        })
+        .collect();
+    let mut writer =
+        ExportWriter::create(output_dir, schema, table, file_columns, format, compress)?;
+
+    let batch_size = options.batch_size.max(1) as i64;
+    let log = StatementLog::new(app_handle, options, schema, table);
+    let mut source = SourceReader::open(
+        source_pool,
+        source_snapshot,
+        &log,
+        &format!("{}.{}", quote_ident(schema), quote_ident(table)),
+        &select_list,
+        row_filter,
+        key_col,
+        None,
+        batch_size,
+        options.max_retries,
+    )
+    .await?;
+
+    let started = std::time::Instant::now();
+    let mut rows_written: i64 = 0;
+    let progress = |rows_written: i64, status: &str| {
+        let (rows_per_second, eta_seconds) =
+            throughput(rows_written, total_rows, started.elapsed());
+        let progress = MigrationProgress {
+            table_name: table.to_string(),
+            current_table,
+            total_tables,
+            rows_transferred: rows_written,
+            total_rows,
+            status: status.to_string(),
+            error: None,
+            rows_per_second,
+            eta_seconds,
+        };
+        let _ = app_handle.emit("migration-progress", &progress);
+    };
+    let mut progress_throttle = ProgressThrottle::default();
+
+    loop {
+        let rows = source.next_batch(cancel_token).await?;
+        for row in &rows {
+            writer.write_row(render_row(row, &columns, &transforms)?)?;
+        }
+        rows_written += rows.len() as i64;
+
+        if (rows.len() as i64) < batch_size {
+            break;
+        }
+        if progress_throttle.due(rows_written, total_rows) {
+            progress(rows_written, "Exporting");
+        }
+        if let Some(max_rows_per_second) = options.max_rows_per_second {
+            throttle(rows_written, max_rows_per_second, started, cancel_token).await?;
+        }
+    }
+    source.close().await;
+    writer.finish()?;
+
+    progress(rows_written, "Complete");
+    Ok((rows_written, warnings))
+}
+
+/// Check that every `column_map` entry maps an existing source column to an existing
+/// target column
+async fn validate_column_map(
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    selection: &TableSelection,
+    target_schema: &str,
+) -> Result<(), MigrateError> {
+    let source = get_table_schema(source_pool, &selection.schema, &selection.name).await?;
+    let target = get_table_schema(target_pool, target_schema, selection.target_table()).await?;
+    if target.columns.is_empty() {
+        return Err(MigrateError::Other(format!(
+            "column_map requires an existing target table {}.{}",
+            target_schema,
+            selection.target_table()
+        )));
+    }
+
+    for (source_column, target_column) in &selection.column_map {
+        if !source.columns.iter().any(|c| &c.name == source_column) {
+            return Err(MigrateError::Other(format!(
+                "Mapped source column {} does not exist",
+                source_column
+            )));
+        }
+        if !target.columns.iter().any(|c| &c.name == target_column) {
+            return Err(MigrateError::Other(format!(
+                "Mapped target column {} does not exist in {}.{}",
+                target_column,
+                target_schema,
+                selection.target_table()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the conflict target columns are covered by a primary key, unique constraint
+/// or (non-partial) unique index on the target table. A table that doesn't exist yet is
+/// created with only the source's primary key, so the columns must match that instead.
+async fn validate_conflict_target(
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    selection: &TableSelection,
+    target_schema: &str,
+    columns: &[String],
+) -> Result<(), MigrateError> {
+    if columns.is_empty() {
+        return Err(MigrateError::Other(
+            "conflict_target must name at least one column".to_string(),
+        ));
+    }
+    let mut wanted: Vec<String> = columns.to_vec();
+    wanted.sort();
+    wanted.dedup();
+
+    let target_table = format!(
+        "{}.{}",
+        quote_ident(target_schema),
+        quote_ident(selection.target_table())
+    );
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(&target_table)
+        .fetch_one(target_pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to check conflict target", e))?;
+
+    let matched = if exists {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM pg_catalog.pg_index i
+                WHERE i.indrelid = to_regclass($1)
+                    AND i.indisunique
+                    AND i.indpred IS NULL
+                    AND i.indexprs IS NULL
+                    AND (
+                        SELECT array_agg(a.attname::text ORDER BY a.attname::text)
+                        FROM pg_catalog.pg_attribute a
+                        WHERE a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+                    ) = (SELECT array_agg(c ORDER BY c) FROM unnest($2::text[]) c)
+            )
+            "#,
+        )
+        .bind(&target_table)
+        .bind(&wanted)
+        .fetch_one(target_pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to check conflict target", e))?
+    } else {
+        let source = get_table_schema(source_pool, &selection.schema, &selection.name).await?;
+        let mut pk: Vec<String> = source
+            .primary_key_columns
+            .iter()
+            .map(|c| selection.target_column(c).to_string())
+            .collect();
+        pk.sort();
+        pk == wanted
+    };
+
+    if matched {
+        Ok(())
+    } else {
+        Err(MigrateError::Other(format!(
+            "conflict_target ({}) is not a primary key or unique constraint on {}.{}",
+            columns.join(", "),
+            target_schema,
+            selection.target_table()
+        )))
+    }
+}
+
+/// Check that excluded columns exist and can be left out of the INSERT. An existing target
+/// table decides nullability; otherwise it's `created_columns`, the columns the DDL will create.
+async fn check_excluded_columns(
+    target_pool: &PgPool,
+    target_schema: &str,
+    table: &str,
+    table_schema: &TableSchema,
+    created_columns: &[ColumnInfo],
+    excluded: &[String],
+) -> Result<(), MigrateError> {
+    let existing = get_table_schema(target_pool, target_schema, table).await?;
+    let target_columns = if existing.columns.is_empty() {
+        created_columns
+    } else {
+        &existing.columns
+    };
+
+    for name in excluded {
+        let source_col = table_schema
+            .columns
+            .iter()
+            .find(|c| &c.name == name)
+            .ok_or_else(|| format!("Excluded column {} does not exist", name))?;
+        if source_col.is_primary_key {
+            return Err(MigrateError::Other(format!(
+                "Primary key column {} cannot be excluded",
+                name
+            )));
+        }
+
+        if let Some(col) = target_columns.iter().find(|c| &c.name == name) {
+            let has_default = col.column_default.is_some()
+                || col.identity_generation.is_some()
+                || col.generation_expression.is_some();
+            if !col.is_nullable && !has_default {
+                return Err(MigrateError::Other(format!(
+                    "Excluded column {} is NOT NULL without a default on the target",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Compare row counts (and optionally checksums) of migrated tables on source and target
+pub async fn verify_tables(
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    tables: &[TableSelection],
+    schema_map: &SchemaMap,
+    checksum: bool,
+) -> Vec<VerifyResult> {
+    let mut results = Vec::new();
+
+    for selection in tables {
+        let target_schema = schema_map.target_schema(&selection.schema);
+        let result = verify_table(source_pool, target_pool, selection, target_schema, checksum)
+            .await
+            .unwrap_or_else(|e| VerifyResult {
+                schema: selection.schema.clone(),
+                table: selection.name.clone(),
+                source_count: 0,
+                target_count: 0,
+                source_checksum: None,
+                target_checksum: None,
+                matched: false,
+                error: Some(e.to_string()),
+            });
+        results.push(result);
+    }
+
+    results
+}
+
+async fn verify_table(
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    selection: &TableSelection,
+    target_schema: &str,
+    checksum: bool,
+) -> Result<VerifyResult, MigrateError> {
+    let schema = selection.schema.as_str();
+    let table = selection.name.as_str();
+    let row_filter = selection.row_filter();
+
+    // `name` only labels a query's result, there's no source table to compare with
+    if selection.source_query.is_some() {
+        return Err(MigrateError::Other(
+            "Rows read from a source query can't be verified".to_string(),
+        ));
+    }
+
+    // The filter names source columns, so it only applies to the target when none were
+    // renamed; otherwise the whole target table is compared
+    let target_filter = if selection.column_map.is_empty() {
+        row_filter
+    } else {
+        None
+    };
+
+    let source_count = get_row_count(source_pool, schema, table, row_filter).await?;
+    let target_table = selection.target_table();
+    let target_count =
+        get_row_count(target_pool, target_schema, target_table, target_filter).await?;
+
+    // Checksums need a stable row order, so they're skipped for tables without a primary key
+    let table_schema = get_table_schema(source_pool, schema, table).await?;
+    let (source_checksum, target_checksum) = if checksum
+        && !table_schema.primary_key_columns.is_empty()
+    {
+        let columns: Vec<&ColumnInfo> = table_schema
+            .columns
+            .iter()
+            .filter(|c| c.generation_expression.is_none())
+            .filter(|c| !selection.exclude_columns.contains(&c.name))
+            .collect();
+        let source_columns: Vec<String> = columns.iter().map(|c| quote_ident(&c.name)).collect();
+        let target_columns: Vec<String> = columns
+            .iter()
+            .map(|c| quote_ident(selection.target_column(&c.name)))
+            .collect();
+        let source_pk: Vec<String> = table_schema
+            .primary_key_columns
+            .iter()
+            .map(|c| quote_ident(c))
+            .collect();
+        let target_pk: Vec<String> = table_schema
+            .primary_key_columns
+            .iter()
+            .map(|c| quote_ident(selection.target_column(c)))
+            .collect();
+
+        (
+            Some(
+                table_checksum(
+                    source_pool,
+                    schema,
+                    table,
+                    &source_columns,
+                    &source_pk,
+                    row_filter,
+                )
+                .await?,
+            ),
+            Some(
+                table_checksum(
+                    target_pool,
+                    target_schema,
+                    target_table,
+                    &target_columns,
+                    &target_pk,
+                    target_filter,
+                )
+                .await?,
+            ),
+        )
+    } else {
+        (None, None)
+    };
+
+    Ok(VerifyResult {
+        schema: schema.to_string(),
+        table: table.to_string(),
+        source_count,
+        target_count,
+        matched: source_count == target_count && source_checksum == target_checksum,
+        source_checksum,
+        target_checksum,
+        error: None,
+    })
+}
+
+/// md5 over the text form of each row's columns, in primary key order
+async fn table_checksum(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    columns: &[String],
+    order_by: &[String],
+    filter: Option<&str>,
+) -> Result<String, MigrateError> {
+    let mut query = format!(
+        "SELECT COALESCE(md5(string_agg(md5(ROW({})::text), '' ORDER BY {})), '') FROM {}.{}",
+        columns.join(", "),
+        order_by.join(", "),
+        quote_ident(schema),
+        quote_ident(table)
+    );
+    if let Some(filter) = filter {
+        query.push_str(&format!(" WHERE ({})", filter));
+    }
+
+    sqlx::query_scalar(&query)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to compute checksum", e))
+}
+
+/// Estimate rows and bytes to copy for the selected tables and flag columns the serializer
+/// can't read, without touching the target. Stops with `Cancelled` once the token is set.
+pub async fn estimate_tables(
+    source_pool: &PgPool,
+    tables: &[TableSelection],
+    options: &MigrationOptions,
+    cancel_token: &CancellationToken,
+) -> Result<MigrationEstimate, MigrateError> {
+    let mut estimate = MigrationEstimate {
+        total_rows: 0,
+        total_bytes: 0,
+        tables_with_unsupported_columns: 0,
+        tables: Vec::new(),
+    };
+
+    for selection in tables {
+        if cancel_token.load(Ordering::Relaxed) {
+            return Err(MigrateError::Cancelled);
+        }
+
+        let table_estimate = estimate_table(source_pool, selection, options)
+            .await
+            .unwrap_or_else(|e| TableEstimate {
+                schema: selection.schema.clone(),
+                table: selection.name.clone(),
+                estimated_rows: 0,
+                size_bytes: 0,
+                unsupported_columns: Vec::new(),
+                error: Some(e.to_string()),
+            });
+
+        estimate.total_rows += table_estimate.estimated_rows;
+        estimate.total_bytes += table_estimate.size_bytes;
+        if !table_estimate.unsupported_columns.is_empty() {
+            estimate.tables_with_unsupported_columns += 1;
+        }
+        estimate.tables.push(table_estimate);
+    }
+
+    Ok(estimate)
+}
+
+async fn estimate_table(
+    source_pool: &PgPool,
+    selection: &TableSelection,
+    options: &MigrationOptions,
+) -> Result<TableEstimate, MigrateError> {
+    let table_schema = get_table_schema(source_pool, &selection.schema, &selection.name).await?;
+    let (mut estimated_rows, mut size_bytes) =
+        get_table_size_estimate(source_pool, &selection.schema, &selection.name).await?;
+
+    // Recreated materialized views are refreshed on the target, so no rows are read
+    let refreshed_on_target = table_schema.matview_definition.is_some()
+        && options.matview_mode == MatviewMode::MaterializedView;
+    let copies_rows = !options.schema_only && !refreshed_on_target;
+    if !copies_rows {
+        estimated_rows = 0;
+        size_bytes = 0;
+    }
+
+    let unsupported_columns = if copies_rows {
+        unsupported_columns(&table_schema, selection)
+    } else {
+        Vec::new()
+    };
+
+    Ok(TableEstimate {
+        schema: selection.schema.clone(),
+        table: selection.name.clone(),
+        estimated_rows,
+        size_bytes,
+        unsupported_columns,
+        error: None,
+    })
+}
+
+/// Columns whose values would be read but that the serializer can't handle
+fn unsupported_columns(
+    table_schema: &TableSchema,
+    selection: &TableSelection,
+) -> Vec<UnsupportedColumn> {
+    table_schema
+        .columns
+        .iter()
+        .filter(|col| col.generation_expression.is_none())
+        .filter(|col| !selection.exclude_columns.contains(&col.name))
+        .filter(|col| !is_supported_type(&col.data_type))
+        .map(|col| UnsupportedColumn {
+            column: col.name.clone(),
+            data_type: col.data_type.clone(),
+        })
+        .collect()
+}
+
+/// Check that the target can accept the migration: not a read-only standby, and the role
+/// can create the target schemas and tables and write to existing tables
+pub async fn check_target(
+    target_pool: &PgPool,
+    tables: &[TableSelection],
+    options: &MigrationOptions,
+    schema_map: &SchemaMap,
+) -> Result<TargetReadiness, MigrateError> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            pg_is_in_recovery() AS in_recovery,
+            current_setting('default_transaction_read_only') = 'on' AS read_only,
+            pg_database_size(current_database()) AS database_size_bytes,
+            has_database_privilege(current_database(), 'CREATE') AS can_create_schema
+        "#,
+    )
+    .fetch_one(target_pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to check target", e))?;
+
+    let in_recovery: bool = row.get("in_recovery");
+    let read_only: bool = row.get("read_only");
+    let can_create_schema: bool = row.get("can_create_schema");
+
+    let mut problems = Vec::new();
+    if in_recovery {
+        problems.push("Target is a standby in recovery and can't be written".to_string());
+    }
+    if read_only {
+        problems.push("Target sessions default to read-only transactions".to_string());
+    }
+
+    let mut checked_schemas = HashSet::new();
+    let mut table_reports = Vec::new();
+    for selection in tables {
+        let target_schema = schema_map.target_schema(&selection.schema);
+
+        if checked_schemas.insert(target_schema.to_string()) {
+            let can_create: Option<bool> = sqlx::query_scalar(
+                "SELECT has_schema_privilege(oid, 'CREATE') FROM pg_namespace WHERE nspname = $1",
+            )
+            .bind(target_schema)
+            .fetch_optional(target_pool)
+            .await
+            .map_err(|e| MigrateError::sql("Failed to check schema privileges", e))?;
+
+            match can_create {
+                Some(false) if options.creates_tables() => {
+                    problems.push(format!("No CREATE privilege on schema {}", target_schema))
+                }
+                None if !can_create_schema => problems.push(format!(
+                    "Schema {} doesn't exist and the role can't create it",
+                    target_schema
+                )),
+                _ => {}
+            }
+        }
+
+        table_reports
+            .push(check_target_table(target_pool, selection, target_schema, options).await?);
+    }
+
+    let ready = problems.is_empty() && table_reports.iter().all(|t| t.problems.is_empty());
+    Ok(TargetReadiness {
+        ready,
+        in_recovery,
+        read_only,
+        database_size_bytes: row.get("database_size_bytes"),
+        problems,
+        tables: table_reports,
+    })
+}
+
+async fn check_target_table(
+    target_pool: &PgPool,
+    selection: &TableSelection,
+    target_schema: &str,
+    options: &MigrationOptions,
+) -> Result<TableReadiness, MigrateError> {
+    let privileges = sqlx::query(
+        r#"
+        SELECT
+            has_table_privilege(c.oid, 'INSERT') AS can_insert,
+            has_table_privilege(c.oid, 'UPDATE') AS can_update,
+            has_table_privilege(c.oid, 'TRUNCATE') AS can_truncate,
+            pg_has_role(c.relowner, 'MEMBER') AS is_owner
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+        "#,
+    )
+    .bind(target_schema)
+    .bind(selection.target_table())
+    .fetch_optional(target_pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to check table privileges", e))?;
+
+    let mut problems = Vec::new();
+    match &privileges {
+        Some(row) => {
+            let writes_rows = !options.schema_only && !options.dry_run;
+            if writes_rows && !row.get::<bool, _>("can_insert") {
+                problems.push("No INSERT privilege".to_string());
+            }
+            if writes_rows
+                && options.conflict_mode == ConflictMode::Upsert
+                && !row.get::<bool, _>("can_update")
+            {
+                problems.push("No UPDATE privilege, needed for upserts".to_string());
+            }
+            if options.truncate_before_insert && !row.get::<bool, _>("can_truncate") {
+                problems.push("No TRUNCATE privilege".to_string());
+            }
+            if options.recreate_table && !row.get::<bool, _>("is_owner") {
+                problems.push("Not the table owner, needed to drop and recreate it".to_string());
+            }
+        }
+        None if !options.creates_tables() => {
+            problems.push("Table doesn't exist and creating tables is disabled".to_string());
+        }
+        None => {}
+    }
+
+    Ok(TableReadiness {
+        schema: target_schema.to_string(),
+        table: selection.target_table().to_string(),
+        exists: privileges.is_some(),
+        problems,
+    })
+}
+
+/// Target tables outside the selection that `TRUNCATE ... CASCADE` of the selected tables
+/// would also empty: every table referencing a truncated one through foreign keys, recursively
+async fn truncate_cascade_tables(
+    target_pool: &PgPool,
+    tables: &[TableSelection],
+    schema_map: &SchemaMap,
+) -> Result<Vec<(String, String)>, MigrateError> {
+    let selected: HashSet<(String, String)> = tables
+        .iter()
+        .map(|t| {
+            let schema = schema_map.target_schema(&t.schema);
+            (schema.to_string(), t.target_table().to_string())
+        })
+        .collect();
+    let dependencies = get_all_dependencies(target_pool).await?;
+
+    let mut truncated = selected.clone();
+    loop {
+        let reached: Vec<(String, String)> = dependencies
+            .iter()
+            .filter(|dep| {
+                !truncated.contains(&(dep.schema.clone(), dep.name.clone()))
+                    && dep
+                        .depends_on
+                        .iter()
+                        .any(|parent| truncated.contains(parent))
+            })
+            .map(|dep| (dep.schema.clone(), dep.name.clone()))
+            .collect();
+        if reached.is_empty() {
+            break;
+        }
+        truncated.extend(reached);
+    }
+
+    let mut affected: Vec<(String, String)> = truncated.difference(&selected).cloned().collect();
+    affected.sort();
+    Ok(affected)
+}
+
+/// Objects outside the selection that `DROP TABLE ... CASCADE` of the selected target tables
+/// would also drop: views and materialized views over them (recursively) and foreign keys of
+/// other tables referencing them. Returned as ("schema.relation", description) pairs.
+async fn drop_cascade_objects(
+    target_pool: &PgPool,
+    tables: &[TableSelection],
+    schema_map: &SchemaMap,
+) -> Result<Vec<(String, String)>, MigrateError> {
+    let (schemas, names): (Vec<String>, Vec<String>) = tables
+        .iter()
+        .map(|t| {
+            let schema = schema_map.target_schema(&t.schema);
+            (schema.to_string(), t.target_table().to_string())
+        })
+        .unzip();
+
+    let rows = sqlx::query(
+        r#"
+        WITH RECURSIVE selected AS (
+            SELECT c.oid
+            FROM unnest($1::text[], $2::text[]) AS t(schema_name, table_name)
+            JOIN pg_namespace n ON n.nspname = t.schema_name
+            JOIN pg_class c ON c.relnamespace = n.oid AND c.relname = t.table_name
+        ),
+        dropped(oid) AS (
+            SELECT oid FROM selected
+            UNION
+            SELECT r.ev_class
+            FROM dropped
+            JOIN pg_depend d ON d.refclassid = 'pg_class'::regclass AND d.refobjid = dropped.oid
+            JOIN pg_rewrite r ON d.classid = 'pg_rewrite'::regclass AND r.oid = d.objid
+            WHERE d.deptype = 'n' AND r.ev_class <> dropped.oid
+        )
+        SELECT
+            format('%s.%s', n.nspname, c.relname) AS relation,
+            pg_describe_object('pg_class'::regclass, c.oid, 0) AS object
+        FROM dropped
+        JOIN pg_class c ON c.oid = dropped.oid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE dropped.oid NOT IN (SELECT oid FROM selected)
+        UNION
+        SELECT
+            format('%s.%s', n.nspname, c.relname),
+            format('foreign key %s on %s.%s', con.conname, n.nspname, c.relname)
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE con.contype = 'f'
+            AND con.confrelid IN (SELECT oid FROM dropped)
+            AND con.conrelid NOT IN (SELECT oid FROM dropped)
+        ORDER BY 1, 2
+        "#,
+    )
+    .bind(&schemas)
+    .bind(&names)
+    .fetch_all(target_pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to list objects dropped with CASCADE", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| (row.get("relation"), row.get("object")))
+        .collect())
+}
+
+/// Map each selected table to the selected tables it depends on (via foreign keys)
+async fn selected_dependencies(
+    pool: &PgPool,
+    tables: &[TableSelection],
+) -> Result<HashMap<(String, String), Vec<(String, String)>>, MigrateError> {
+    let selected: HashSet<(String, String)> = tables
+        .iter()
+        .map(|t| (t.schema.clone(), t.name.clone()))
+        .collect();
+
+    let dependencies = get_all_dependencies(pool)
+        .await?
+        .into_iter()
+        .filter_map(|dep| {
+            let key = (dep.schema, dep.name);
+            if !selected.contains(&key) {
+                return None;
+            }
+            let parents = dep
+                .depends_on
+                .into_iter()
+                .filter(|parent| selected.contains(parent))
+                .collect();
+            Some((key, parents))
+        })
+        .collect();
+
+    Ok(dependencies)
+}
+
+/// Column used for keyset pagination: the selection's `pagination_column` when it is indexed,
+/// otherwise the first primary key column. `None` means the table is read through a cursor.
+async fn pagination_column(
+    pool: &PgPool,
+    selection: &TableSelection,
+    table_schema: &TableSchema,
+    warnings: &mut Vec<String>,
+) -> Result<Option<ColumnInfo>, MigrateError> {
+    let primary_key = table_schema
+        .primary_key_columns
+        .first()
+        .and_then(|pk| table_schema.columns.iter().find(|c| &c.name == pk))
+        .cloned();
+    let Some(name) = selection
+        .pagination_column
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    else {
+        return Ok(primary_key);
+    };
+
+    // A query result has no indexes to check; the key is taken as given
+    if selection.source_query.is_some() {
+        return table_schema
+            .columns
+            .iter()
+            .find(|c| c.name == name)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| {
+                MigrateError::Other(format!(
+                    "Pagination column {} is not a column of the source query",
+                    name
+                ))
+            });
+    }
+
+    // The key is read back from each fetched row, so it must be one of the copied columns
+    let column = table_schema
+        .columns
+        .iter()
+        .find(|c| {
+            c.name == name
+                && c.generation_expression.is_none()
+                && !selection.exclude_columns.contains(&c.name)
+        })
+        .ok_or_else(|| {
+            MigrateError::Other(format!(
+                "Pagination column {} is not a copied column of {}.{}",
+                name, selection.schema, selection.name
+            ))
+        })?;
+
+    // NULL when no valid, non-partial index leads with the column
+    let unique: Option<bool> = sqlx::query_scalar(
+        r#"
+        SELECT bool_or(i.indisunique AND i.indnkeyatts = 1)
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = i.indkey[0]
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND a.attname = $3
+            AND i.indisvalid
+            AND i.indpred IS NULL
+        "#,
+    )
+    .bind(&selection.schema)
+    .bind(&selection.name)
+    .bind(name)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to check pagination column", e))?;
+
+    match unique {
+        None => {
+            warnings.push(format!(
+                "pagination column {} is not indexed; using the default pagination instead",
+                name
+            ));
+            return Ok(primary_key);
+        }
+        Some(false) => warnings.push(format!(
+            "pagination column {} is not unique; rows sharing a value at a batch boundary may be skipped",
+            name
+        )),
+        Some(true) => {}
+    }
+    if column.is_nullable {
+        warnings.push(format!(
+            "pagination column {} is nullable; rows where it is NULL may be skipped",
+            name
+        ));
+    }
+
+    Ok(Some(column.clone()))
+}
+
+/// Number of rows of a source query, counted on the server
+async fn count_query_rows(
+    pool: &PgPool,
+    source_relation: &str,
+    filter: Option<&str>,
+) -> Result<i64, MigrateError> {
+    let mut query = format!("SELECT COUNT(*) FROM {}", source_relation);
+    if let Some(filter) = filter {
+        query.push_str(&format!(" WHERE ({})", filter));
+    }
+
+    sqlx::query_scalar(&query)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to count rows", e))
+}
+
+/// Sends the statements run for one table as `migration-log` events; does nothing unless
+/// `verbose` is set
+struct StatementLog<'a> {
+    app_handle: Option<&'a AppHandle>,
+    table: String,
+}
+
+impl<'a> StatementLog<'a> {
+    fn new(
+        app_handle: &'a AppHandle,
+        options: &MigrationOptions,
+        schema: &str,
+        table: &str,
+    ) -> Self {
+        Self {
+            app_handle: options.verbose.then_some(app_handle),
+            table: format!("{}.{}", schema, table),
+        }
+    }
+
+    fn source(&self, statement: &str, rows: Option<usize>) {
+        self.emit("source", statement, rows);
+    }
+
+    fn target(&self, statement: &str, rows: Option<usize>) {
+        self.emit("target", statement, rows);
+    }
+
+    fn emit(&self, database: &str, statement: &str, rows: Option<usize>) {
+        let Some(app_handle) = self.app_handle else {
+            return;
+        };
+        let statement = statement.trim();
+        let statement = match statement.char_indices().nth(MAX_LOGGED_STATEMENT_CHARS) {
+            Some((end, _)) => format!("{}...", &statement[..end]),
+            None => statement.to_string(),
+        };
+        let _ = app_handle.emit(
+            "migration-log",
+            &MigrationLog {
+                table: self.table.clone(),
+                database: database.to_string(),
+                statement,
+                rows,
+            },
+        );
+    }
+}
+
+/// Source transaction a table is read through
+enum SourceTransaction<'a> {
+    /// Opened just for this table's cursor
+    Own(sqlx::Transaction<'static, sqlx::Postgres>),
+    /// The migration-wide snapshot, held for the whole table
+    Snapshot(tokio::sync::MutexGuard<'a, SourceSnapshot>),
+}
+
+impl SourceTransaction<'_> {
+    fn conn(&mut self) -> &mut PgConnection {
+        match self {
+            SourceTransaction::Own(tx) => tx,
+            SourceTransaction::Snapshot(snapshot) => &mut snapshot.tx,
+        }
+    }
+}
+
+/// Reads a source table in batches: keyset pagination on the key column (much faster than
+/// OFFSET), or without one a single stable pass through a server-side cursor (LIMIT/OFFSET is
+/// quadratic and can reorder rows between batches)
+struct SourceReader<'a> {
+    pool: &'a PgPool,
+    log: &'a StatementLog<'a>,
+    tx: Option<SourceTransaction<'a>>,
+    source_full_table: String,
+    select_list: String,
+    row_filter: Option<String>,
+    key_col: Option<ColumnInfo>,
+    last_key_value: Option<String>, // SQL literal of the key of the last row read
+    batch_size: i64,
+    max_retries: usize,
+}
+
+impl<'a> SourceReader<'a> {
+    #[allow(clippy::too_many_arguments)]
+    async fn open(
+        pool: &'a PgPool,
+        snapshot: Option<&'a SharedSnapshot>,
+        log: &'a StatementLog<'a>,
+        source_full_table: &str,
+        select_list: &str,
+        row_filter: Option<&str>,
+        key_col: Option<ColumnInfo>,
+        resume_from: Option<String>,
+        batch_size: i64,
+        max_retries: usize,
+    ) -> Result<SourceReader<'a>, MigrateError> {
+        let mut tx = match snapshot {
+            Some(snapshot) => {
+                // Each table reads inside a savepoint, so a failed statement doesn't abort the
+                // snapshot for the tables after it
+                let mut snapshot = snapshot.lock().await;
+                if snapshot.reading {
+                    let _ = sqlx::query("ROLLBACK TO SAVEPOINT pgm_source_table")
+                        .execute(&mut *snapshot.tx)
+                        .await;
+                } else {
+                    sqlx::query("SAVEPOINT pgm_source_table")
+                        .execute(&mut *snapshot.tx)
+                        .await
+                        .map_err(|e| MigrateError::sql("Failed to read source snapshot", e))?;
+                    snapshot.reading = true;
+                }
+                Some(SourceTransaction::Snapshot(snapshot))
+            }
+            None if key_col.is_none() => {
+                Some(SourceTransaction::Own(pool.begin().await.map_err(|e| {
+                    MigrateError::sql("Failed to begin source transaction", e)
+                })?))
+            }
+            None => None,
+        };
+
+        if key_col.is_none() {
+            let where_clause = row_filter
+                .map(|filter| format!("WHERE ({})", filter))
+                .unwrap_or_default();
+            if let Some(ref mut tx) = tx {
+                let declare = format!(
+                    "DECLARE pgm_source_cursor NO SCROLL CURSOR FOR SELECT {} FROM {} {}",
+                    select_list, source_full_table, where_clause
+                );
+                log.source(&declare, None);
+                sqlx::query(&declare)
+                    .execute(tx.conn())
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to open source cursor", e))?;
+            }
+        }
+
+        Ok(SourceReader {
+            pool,
+            log,
+            tx,
+            source_full_table: source_full_table.to_string(),
+            select_list: select_list.to_string(),
+            row_filter: row_filter.map(str::to_string),
+            key_col,
+            last_key_value: resume_from,
+            batch_size,
+            max_retries,
+        })
+    }
+
+    /// Next batch of rows; empty once the table is exhausted
+    async fn next_batch(
+        &mut self,
+        cancel_token: &CancellationToken,
+    ) -> Result<Vec<PgRow>, MigrateError> {
+        if cancel_token.load(Ordering::Relaxed) {
+            return Err(MigrateError::Cancelled);
+        }
+
+        let rows: Vec<PgRow> = match (&self.key_col, &mut self.tx) {
+            (None, Some(tx)) => {
+                let fetch = format!("FETCH {} FROM pgm_source_cursor", self.batch_size);
+                let rows = sqlx::query(&fetch)
+                    .fetch_all(tx.conn())
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to fetch data", e))?;
+                self.log.source(&fetch, Some(rows.len()));
+                rows
+            }
+            (key_col, tx) => {
+                let select_query = keyset_query(
+                    &self.select_list,
+                    &self.source_full_table,
+                    key_col
+                        .as_ref()
+                        .map(|k| k.name.as_str())
+                        .unwrap_or_default(),
+                    self.last_key_value.as_deref(),
+                    self.row_filter.as_deref(),
+                    self.batch_size,
+                );
+
+                let rows = match tx {
+                    // A failed statement aborts the snapshot, so it can't be retried in place
+                    Some(tx) => sqlx::query(&select_query).fetch_all(tx.conn()).await,
+                    None => {
+                        with_retry(self.max_retries, cancel_token, || {
+                            sqlx::query(&select_query).fetch_all(self.pool)
+                        })
+                        .await
+                    }
+                }
+                .map_err(|e| MigrateError::sql("Failed to fetch data", e))?;
+                self.log.source(&select_query, Some(rows.len()));
+                rows
+            }
+        };
+
+        // Track the last key for the next batch
+        if let (Some(key), Some(row)) = (&self.key_col, rows.last()) {
+            self.last_key_value = Some(get_column_value_as_sql(row, &key.name, &key.data_type)?);
+        }
+
+        Ok(rows)
+    }
+
+    /// Keyset position after the last batch read
+    fn last_key_value(&self) -> Option<&String> {
+        self.last_key_value.as_ref()
+    }
+
+    /// Close the source cursor. The transactions only read, so rolling back is enough; for
+    /// the snapshot that's a rollback to the table's savepoint, which keeps the snapshot.
+    async fn close(self) {
+        match self.tx {
+            Some(SourceTransaction::Own(tx)) => {
+                let _ = tx.rollback().await;
+            }
+            Some(SourceTransaction::Snapshot(mut snapshot)) => {
+                let mut released = sqlx::query("ROLLBACK TO SAVEPOINT pgm_source_table")
+                    .execute(&mut *snapshot.tx)
+                    .await
+                    .is_ok();
+                if released {
+                    released = sqlx::query("RELEASE SAVEPOINT pgm_source_table")
+                        .execute(&mut *snapshot.tx)
+                        .await
+                        .is_ok();
+                }
+                snapshot.reading = !released;
+            }
+            None => {}
+        }
+    }
+}
+
+/// Split the source columns into those the target can take, in the target's column order, and
+/// those it lacks or generates itself
+fn order_by_target_columns(
+    insert_columns: Vec<ColumnInfo>,
+    target_columns: &[ColumnInfo],
+    selection: &TableSelection,
+) -> (Vec<ColumnInfo>, Vec<ColumnInfo>) {
+    let target_position = |col: &ColumnInfo| {
+        target_columns
+            .iter()
+            .find(|t| t.name == selection.target_column(&col.name))
+            .filter(|t| t.generation_expression.is_none())
+            .map(|t| t.ordinal_position)
+    };
+    let (mut kept, skipped): (Vec<ColumnInfo>, Vec<ColumnInfo>) = insert_columns
+        .into_iter()
+        .partition(|c| target_position(c).is_some());
+    kept.sort_by_key(|c| target_position(c));
+    (kept, skipped)
+}
+
+/// Next page of a keyset-paginated read: the rows after `last_key_value` (a SQL literal) in
+/// key order
+fn keyset_query(
+    select_list: &str,
+    source_full_table: &str,
+    key_column: &str,
+    last_key_value: Option<&str>,
+    row_filter: Option<&str>,
+    batch_size: i64,
+) -> String {
+    let mut conditions = Vec::new();
+    if let Some(last_val) = last_key_value {
+        conditions.push(format!("{} > {}", quote_ident(key_column), last_val));
+    }
+    if let Some(filter) = row_filter {
+        conditions.push(format!("({})", filter));
+    }
+    let where_clause = if conditions.is_empty() {
+        "".to_string()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    format!(
+        "SELECT {} FROM {} {} ORDER BY {} LIMIT {}",
+        select_list,
+        source_full_table,
+        where_clause,
+        quote_ident(key_column),
+        batch_size
+    )
+}
+
+/// Migrate a single table, returning the rows moved and any non-fatal warnings
+#[allow(clippy::too_many_arguments)]
+async fn migrate_single_table(
+    app_handle: &AppHandle,
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    selection: &TableSelection,
+    options: &MigrationOptions,
+    cancel_token: &CancellationToken,
+    pause_token: &PauseToken,
+    current_table: usize,
+    total_tables: usize,
+    schema_map: &SchemaMap,
+    checkpoint: Option<&CheckpointFile>,
+    shared_tx: Option<&SharedTransaction>,
+    source_snapshot: Option<&SharedSnapshot>,
+) -> Result<(i64, Vec<String>), MigrateError> {
+    let schema = selection.schema.as_str();
+    let table = selection.name.as_str();
+    let row_filter = selection.row_filter();
+    let target_schema = schema_map.target_schema(schema);
+    let target_table = selection.target_table();
+    let source_full_table = selection.source_relation();
+    // Indexes, comments, sequences and the types to install only exist for a source table
+    let from_table = selection.source_query.is_none();
+    let target_full_table = format!(
+        "{}.{}",
+        quote_ident(target_schema),
+        quote_ident(target_table)
+    );
+    let log = StatementLog::new(app_handle, options, schema, table);
+
+    // Get source table schema and row count
+    let mut table_schema = match selection.source_query {
+        Some(ref query) => get_query_schema(source_pool, &query.sql, schema, target_table).await?,
+        None => get_table_schema(source_pool, schema, table).await?,
+    };
+    if !selection.exclude_columns.is_empty() {
+        let created_columns = if selection.exclude_columns_from_ddl {
+            Vec::new()
+        } else {
+            table_schema.columns.clone()
+        };
+        check_excluded_columns(
+            target_pool,
+            target_schema,
+            target_table,
+            &table_schema,
+            &created_columns,
+            &selection.exclude_columns,
+        )
+        .await?;
+
+        if selection.exclude_columns_from_ddl {
+            table_schema
+                .columns
+                .retain(|c| !selection.exclude_columns.contains(&c.name));
+            table_schema.create_statement = generate_create_table_statement(
+                schema,
+                table,
+                &table_schema.columns,
+                &table_schema.primary_key_columns,
+                &table_schema.check_constraints,
+                table_schema.partition_key.as_deref(),
+                false,
+            );
+        }
+    }
+
+    // Fail before any DDL instead of on the first row with an unreadable value, possibly after
+    // other tables were already migrated
+    let refreshed_on_target = table_schema.matview_definition.is_some()
+        && options.matview_mode == MatviewMode::MaterializedView;
+    if !options.schema_only && !refreshed_on_target {
+        let unsupported = unsupported_columns(&table_schema, selection);
+        if !unsupported.is_empty() {
+            let columns: Vec<String> = unsupported
+                .iter()
+                .map(|c| format!("{} ({})", c.column, c.data_type))
+                .collect();
+            return Err(MigrateError::Other(format!(
+                "Unsupported column types: {}. Exclude these columns to migrate the rest of the table.",
+                columns.join(", ")
+            )));
+        }
+    }
+
+    let mut preflight_warnings = Vec::new();
+    let key_col = pagination_column(
+        source_pool,
+        selection,
+        &table_schema,
+        &mut preflight_warnings,
+    )
+    .await?;
+
+    // Counting can be slow on big tables, and no rows are moved in schema-only mode. An empty
+    // source is detected without scanning the table.
+    let (has_rows, total_rows) = if options.schema_only {
+        (false, 0)
+    } else if from_table {
+        let has_rows = table_has_rows(source_pool, schema, table, row_filter).await?;
+        let total_rows = if has_rows {
+            get_row_count(source_pool, schema, table, row_filter).await?
+        } else {
+            0
+        };
+        (has_rows, total_rows)
+    } else {
+        // The query runs once just to count its rows
+        let total_rows = count_query_rows(source_pool, &source_full_table, row_filter).await?;
+        (total_rows > 0, total_rows)
+    };
+    let on_conflict = conflict_clause(
+        options.conflict_mode,
+        options.conflict_target.as_deref(),
+        &table_schema,
+        selection,
+    )?;
+
+    // Resume a partially migrated table from its last checkpointed key
+    let resume_from = match (checkpoint, &key_col) {
+        (Some(checkpoint), Some(_)) => checkpoint.resume_position(target_schema, target_table),
+        _ => None,
+    };
+
+    // Report a setup step before any rows have moved
+    let emit_status = |status: &str| {
+        let progress = MigrationProgress {
+            table_name: table.to_string(),
+            current_table,
+            total_tables,
+            rows_transferred: 0,
+            total_rows,
+            status: status.to_string(),
+            error: None,
+            rows_per_second: 0.0,
+            eta_seconds: None,
+        };
+        let _ = app_handle.emit("migration-progress", &progress);
+    };
+
+    // Emit initial progress
+    emit_status("Preparing");
+
+    // Dry run: report what would be moved without touching the target
+    if options.dry_run {
+        let progress = MigrationProgress {
+            table_name: table.to_string(),
+            current_table,
+            total_tables,
+            rows_transferred: total_rows,
+            total_rows,
+            status: "DryRun".to_string(),
+            error: None,
+            rows_per_second: 0.0,
+            eta_seconds: None,
+        };
+        let _ = app_handle.emit("migration-progress", &progress);
+        return Ok((total_rows, Vec::new()));
+    }
+
+    // Run the DDL and the load over a single target connection: the shared transaction's
+    // with atomic_all, otherwise one from the pool
+    let in_shared_tx = shared_tx.is_some();
+    let in_transaction = options.transactional || in_shared_tx;
+    let mut pooled_conn;
+    let mut shared_guard;
+    let target_conn: &mut PgConnection = match shared_tx {
+        Some(shared_tx) => {
+            shared_guard = shared_tx.lock().await;
+            &mut shared_guard
+        }
+        None => {
+            pooled_conn = target_pool
+                .acquire()
+                .await
+                .map_err(|e| MigrateError::sql("Failed to acquire target connection", e))?;
+            &mut pooled_conn
+        }
+    };
+
+    // Resolve unqualified types and functions in copied defaults against the target schema
+    // first, whatever the server's search_path is. Reset below once the table is done.
+    let set_search_path = format!("SET search_path TO {}, public", quote_ident(target_schema));
+    log.target(&set_search_path, None);
+    sqlx::query(&set_search_path)
+        .execute(&mut *target_conn)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to set search_path", e))?;
+
+    let result = async {
+        // Ensure target schema exists
+        emit_status("Creating schema");
+        let schema_query = format!("CREATE SCHEMA IF NOT EXISTS {}", quote_ident(target_schema));
+        log.target(&schema_query, None);
+        execute_tolerant(target_conn, &schema_query, in_shared_tx).await;
+
+        // Recreate a materialized view from its definition and let the target compute its rows
+        if let (Some(definition), MatviewMode::MaterializedView) =
+            (&table_schema.matview_definition, options.matview_mode)
+        {
+            emit_status("Creating materialized view");
+            let create_view = format!(
+                "CREATE MATERIALIZED VIEW IF NOT EXISTS {} AS {} WITH NO DATA",
+                target_full_table,
+                definition.trim().trim_end_matches(';')
+            );
+            log.target(&create_view, None);
+            sqlx::query(&create_view)
+                .execute(&mut *target_conn)
+                .await
+                .map_err(|e| MigrateError::sql("Failed to create materialized view", e))?;
+
+            if !options.schema_only {
+                emit_status("Refreshing materialized view");
+                let refresh = format!("REFRESH MATERIALIZED VIEW {}", target_full_table);
+                log.target(&refresh, None);
+                sqlx::query(&refresh)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to refresh materialized view", e))?;
+            }
+
+            let progress = MigrationProgress {
+                table_name: table.to_string(),
+                current_table,
+                total_tables,
+                rows_transferred: 0,
+                total_rows: 0,
+                status: "Complete".to_string(),
+                error: None,
+                rows_per_second: 0.0,
+                eta_seconds: None,
+            };
+            let _ = app_handle.emit("migration-progress", &progress);
+            return Ok((0, Vec::new()));
+        }
+
+        // Identity columns, OVERRIDING SYSTEM VALUE and declarative partitioning need PostgreSQL
+        // 10, generated columns PostgreSQL 12
+        let target_version = server_version_num(target_pool).await?;
+        let creates_table = options.creates_tables();
+        if creates_table && target_version < 100000 && table_schema.partition_key.is_some() {
+            return Err(MigrateError::Other(format!(
+                "{} is partitioned, which requires PostgreSQL 10 or later on the target",
+                source_full_table
+            )));
+        }
+        if let Some(col) = table_schema
+            .columns
+            .iter()
+            .find(|c| c.generation_expression.is_some())
+            .filter(|_| creates_table && target_version < 120000)
+        {
+            return Err(MigrateError::Other(format!(
+                "Column {} is generated, which requires PostgreSQL 12 or later on the target",
+                col.name
+            )));
+        }
+
+        // Read the existing target table before CREATE TABLE IF NOT EXISTS, which leaves it as is
+        let existing_target = if options.add_missing_columns && !options.recreate_table {
+            Some(get_table_schema(target_pool, target_schema, target_table).await?)
+                .filter(|t| !t.columns.is_empty())
+        } else {
+            None
+        };
+
+        // Create table if needed
+        if creates_table {
+            // Objects in the table's own schema (partitions, preserved sequences) follow it to the
+            // target schema
+            let map_schema = |s: &str| {
+                if s == schema {
+                    target_schema.to_string()
+                } else {
+                    s.to_string()
+                }
+            };
+
+            // Install extensions providing column types (hstore, citext, ...) first
+            let extensions = if from_table {
+                get_table_extensions(source_pool, schema, table).await?
+            } else {
+                Vec::new()
+            };
+            for extension in &extensions {
+                let schema_query = format!(
+                    "CREATE SCHEMA IF NOT EXISTS {}",
+                    quote_ident(&extension.schema)
+                );
+                log.target(&schema_query, None);
+                execute_tolerant(target_conn, &schema_query, in_shared_tx).await;
+
+                let create_extension = generate_create_extension_statement(extension);
+                log.target(&create_extension, None);
+                sqlx::query(&create_extension)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| {
+                        MigrateError::sql(
+                            &format!("Failed to create extension {}", extension.name),
+                            e,
+                        )
+                    })?;
+            }
+
+            // Create enum types used by the table first (they keep their source schema)
+            let enum_types = if from_table {
+                get_table_enums(source_pool, schema, table).await?
+            } else {
+                Vec::new()
+            };
+            for enum_type in &enum_types {
+                let schema_query = format!(
+                    "CREATE SCHEMA IF NOT EXISTS {}",
+                    quote_ident(&enum_type.schema)
+                );
+                log.target(&schema_query, None);
+                execute_tolerant(target_conn, &schema_query, in_shared_tx).await;
+
+                let create_enum = generate_create_enum_statement(enum_type);
+                log.target(&create_enum, None);
+                sqlx::query(&create_enum)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| {
+                        MigrateError::sql(
+                            &format!("Failed to create enum type {}", enum_type.name),
+                            e,
+                        )
+                    })?;
+            }
+
+            // Then composite types, which may have enum fields (also kept in their source schema)
+            let composites = if from_table {
+                get_table_composites(source_pool, schema, table).await?
+            } else {
+                Vec::new()
+            };
+            for composite in &composites {
+                let schema_query = format!(
+                    "CREATE SCHEMA IF NOT EXISTS {}",
+                    quote_ident(&composite.schema)
+                );
+                log.target(&schema_query, None);
+                execute_tolerant(target_conn, &schema_query, in_shared_tx).await;
+
+                let create_composite = generate_create_composite_statement(composite);
+                log.target(&create_composite, None);
+                sqlx::query(&create_composite)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| {
+                        MigrateError::sql(
+                            &format!("Failed to create composite type {}", composite.name),
+                            e,
+                        )
+                    })?;
+            }
+
+            // Resuming continues loading into the table created by the interrupted run
+            if options.recreate_table && resume_from.is_none() {
+                emit_status("Dropping table");
+                let drop_table = format!("DROP TABLE IF EXISTS {} CASCADE", target_full_table);
+                log.target(&drop_table, None);
+                sqlx::query(&drop_table)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to drop table", e))?;
+            }
+
+            // Create the table in the target schema; sequence defaults follow it there
+            emit_status("Creating table");
+            let mut ddl_columns = table_schema.columns.clone();
+            let mut owned_sequences = if options.preserve_sequences && from_table {
+                get_owned_sequences(source_pool, schema, table).await?
+            } else {
+                Vec::new()
+            };
+            for owned in &mut owned_sequences {
+                owned.schema = map_schema(&owned.schema);
+            }
+            if target_version < 100000 {
+                // No identity columns before PostgreSQL 10: create them as SERIAL instead, which
+                // generate_create_table_statement does for any nextval() default. Preserved
+                // sequences are used by name.
+                for col in &mut ddl_columns {
+                    if col.identity_generation.take().is_some() {
+                        let sequence = owned_sequences
+                            .iter()
+                            .find(|o| o.column == col.name)
+                            .map(|o| {
+                                format!(
+                                    "{}.{}",
+                                    quote_ident(&o.schema),
+                                    quote_ident(&o.sequence.name)
+                                )
+                            })
+                            .unwrap_or_else(|| {
+                                format!(
+                                    "{}.{}",
+                                    quote_ident(target_schema),
+                                    quote_ident(&format!("{}_{}_seq", target_table, col.name))
+                                )
+                            });
+                        col.column_default = Some(format!("nextval({})", quote_literal(&sequence)));
+                    }
+                }
+            }
+            // Identity columns create their own sequence
+            owned_sequences.retain(|o| {
+                ddl_columns
+                    .iter()
+                    .any(|c| c.name == o.column && c.identity_generation.is_none())
+            });
+            for owned in &owned_sequences {
+                let schema_query =
+                    format!("CREATE SCHEMA IF NOT EXISTS {}", quote_ident(&owned.schema));
+                log.target(&schema_query, None);
+                execute_tolerant(target_conn, &schema_query, in_shared_tx).await;
+
+                let create_sequence = generate_create_sequence_statement(
+                    &owned.sequence,
+                    &owned.schema,
+                    target_version,
+                );
+                log.target(&create_sequence, None);
+                sqlx::query(&create_sequence)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| {
+                        MigrateError::sql(
+                            &format!("Failed to create sequence {}", owned.sequence.name),
+                            e,
+                        )
+                    })?;
+            }
+            if target_schema != schema {
+                for col in &mut ddl_columns {
+                    col.column_default = col
+                        .column_default
+                        .as_deref()
+                        .map(|d| retarget_sequence_default(d, schema, target_schema));
+                }
+            }
+            let create_sql = |columns: &[ColumnInfo]| {
+                generate_create_table_statement(
+                    target_schema,
+                    target_table,
+                    columns,
+                    &table_schema.primary_key_columns,
+                    &table_schema.check_constraints,
+                    table_schema.partition_key.as_deref(),
+                    options.preserve_sequences,
+                )
+                .replacen("CREATE TABLE", "CREATE TABLE IF NOT EXISTS", 1)
+            };
+
+            let create_table = create_sql(&ddl_columns);
+            log.target(&create_table, None);
+            if let Err(e) = execute_savepointed(target_conn, &create_table, in_shared_tx).await {
+                // A default may call a function or sequence that doesn't exist on the target:
+                // create the table without copied defaults, then add them back one at a time
+                let has_verbatim_default =
+                    |col: &ColumnInfo| has_verbatim_default(col, options.preserve_sequences);
+                if !ddl_columns.iter().any(has_verbatim_default) {
+                    return Err(MigrateError::sql("Failed to create table", e));
+                }
+                let without_defaults: Vec<ColumnInfo> = ddl_columns
+                    .iter()
+                    .map(|col| ColumnInfo {
+                        column_default: if has_verbatim_default(col) {
+                            None
+                        } else {
+                            col.column_default.clone()
+                        },
+                        ..col.clone()
+                    })
+                    .collect();
+                let create_table = create_sql(&without_defaults);
+                log.target(&create_table, None);
+                sqlx::query(&create_table)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|_| MigrateError::sql("Failed to create table", e))?;
+
+                for col in ddl_columns.iter().filter(|c| has_verbatim_default(c)) {
+                    let default = col.column_default.as_deref().unwrap_or_default();
+                    let set_default = format!(
+                        "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
+                        target_full_table,
+                        quote_ident(&col.name),
+                        default
+                    );
+                    log.target(&set_default, None);
+                    if !execute_tolerant(target_conn, &set_default, in_shared_tx).await {
+                        preflight_warnings.push(format!(
+                            "default {} of column {} could not be created on the target and was dropped",
+                            default, col.name
+                        ));
+                    }
+                }
+            }
+
+            // Preserved sequences belong to the new table's columns, so dropping the table drops
+            // them and sync_sequences finds them
+            for owned in &owned_sequences {
+                let owned_by = format!(
+                    "ALTER SEQUENCE {}.{} OWNED BY {}.{}",
+                    quote_ident(&owned.schema),
+                    quote_ident(&owned.sequence.name),
+                    target_full_table,
+                    quote_ident(&owned.column)
+                );
+                log.target(&owned_by, None);
+                sqlx::query(&owned_by)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to set sequence owner", e))?;
+            }
+
+            // Recreate partitions so rows inserted through the parent have somewhere to go.
+            // Partitions in the table's own schema follow it to the target schema, and are
+            // renamed along with the table.
+            for partition in &table_schema.partitions {
+                let partition_schema = map_schema(&partition.schema);
+                let schema_query = format!(
+                    "CREATE SCHEMA IF NOT EXISTS {}",
+                    quote_ident(&partition_schema)
+                );
+                log.target(&schema_query, None);
+                execute_tolerant(target_conn, &schema_query, in_shared_tx).await;
+
+                let create_partition = generate_create_partition_statement(
+                    &PartitionInfo {
+                        name: renamed_for_target(&partition.name, table, target_table),
+                        parent_name: renamed_for_target(
+                            &partition.parent_name,
+                            table,
+                            target_table,
+                        ),
+                        ..partition.clone()
+                    },
+                    &partition_schema,
+                    &map_schema(&partition.parent_schema),
+                );
+                log.target(&create_partition, None);
+                sqlx::query(&create_partition)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| {
+                        MigrateError::sql(
+                            &format!("Failed to create partition {}", partition.name),
+                            e,
+                        )
+                    })?;
+            }
+        }
+
+        // Bring an existing target table up to date with columns added on the source since
+        if let Some(existing) = &existing_target {
+            let missing = missing_target_columns(&table_schema.columns, existing)?;
+            if !missing.is_empty() {
+                emit_status("Adding columns");
+            }
+            for col in missing {
+                let col = ColumnInfo {
+                    column_default: col
+                        .column_default
+                        .as_deref()
+                        .map(|d| retarget_sequence_default(d, schema, target_schema)),
+                    ..col.clone()
+                };
+                let add_column = format!(
+                    "ALTER TABLE {} ADD COLUMN {}",
+                    target_full_table,
+                    generate_column_definition(&col, false)
+                );
+                log.target(&add_column, None);
+                sqlx::query(&add_column)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| {
+                        MigrateError::sql(&format!("Failed to add column {}", col.name), e)
+                    })?;
+            }
+        }
+
+        // Carry over table and column comments
+        if options.include_comments && from_table {
+            let comments = get_comments(source_pool, schema, table).await?;
+            for statement in generate_comment_statements(&comments, target_schema, target_table) {
+                log.target(&statement, None);
+                sqlx::query(&statement)
+                    .execute(&mut *target_conn)
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to set comment", e))?;
+            }
+        }
+
+        // Schema only: the structure is in place, leave loading the rows to another tool
+        if options.schema_only {
+            if options.include_indexes && from_table {
+                create_indexes(
+                    source_pool,
+                    target_conn,
+                    schema,
+                    table,
+                    target_schema,
+                    target_table,
+                    &log,
+                )
+                .await?;
+            }
+            let progress = MigrationProgress {
+                table_name: table.to_string(),
+                current_table,
+                total_tables,
+                rows_transferred: 0,
+                total_rows: 0,
+                status: "Complete".to_string(),
+                error: None,
+                rows_per_second: 0.0,
+                eta_seconds: None,
+            };
+            let _ = app_handle.emit("migration-progress", &progress);
+            return Ok((0, preflight_warnings));
+        }
+
+        // Generated columns are computed by the target, so they can't be inserted
+        let mut insert_columns: Vec<ColumnInfo> = table_schema
+            .columns
+            .iter()
+            .filter(|c| c.generation_expression.is_none())
+            .filter(|c| !selection.exclude_columns.contains(&c.name))
+            .cloned()
+            .collect();
+
+        // Insert into the columns the target table actually has, in its order, so a table created
+        // elsewhere may order its columns differently or lack some. Nothing is known about a table
+        // created inside the shared transaction, which other connections can't see yet.
+        let target_columns = get_table_schema(target_pool, target_schema, target_table)
+            .await?
+            .columns;
+        if !target_columns.is_empty() {
+            let (kept, skipped) =
+                order_by_target_columns(insert_columns, &target_columns, selection);
+            for col in &skipped {
+                preflight_warnings.push(format!(
+                    "column {} is not an insertable column of {} and was skipped",
+                    selection.target_column(&col.name),
+                    target_full_table
+                ));
+            }
+            if kept.is_empty() {
+                return Err(MigrateError::Other(format!(
+                    "None of the source columns exist in {}",
+                    target_full_table
+                )));
+            }
+            insert_columns = kept;
+        }
+
+        // Build column list (target names; values are still selected by source name)
+        let columns: Vec<String> = insert_columns
+            .iter()
+            .map(|c| quote_ident(selection.target_column(&c.name)))
+            .collect();
+        let column_list = columns.join(", ");
+
+        // Keep source values for GENERATED ALWAYS identity columns
+        let overriding = if target_version >= 100000
+            && insert_columns
+                .iter()
+                .any(|c| c.identity_generation.as_deref() == Some("ALWAYS"))
+        {
+            " OVERRIDING SYSTEM VALUE"
+        } else {
+            ""
+        };
+
+        // Primary key columns that are read, to identify rows rejected with isolate_failures
+        let key_columns: Vec<ColumnInfo> = insert_columns
+            .iter()
+            .filter(|c| table_schema.primary_key_columns.contains(&c.name))
+            .cloned()
+            .collect();
+
+        let transforms = compile_transforms(selection, &table_schema.columns, &insert_columns)?;
+
+        // Build select list (some types are read through a cast)
+        let select_exprs: Vec<String> = insert_columns.iter().map(select_column_expr).collect();
+        let select_list = select_exprs.join(", ");
+
+        // Load data in one transaction (already open with atomic_all)
+        if options.transactional && !in_shared_tx {
+            sqlx::query("BEGIN")
+                .execute(&mut *target_conn)
+                .await
+                .map_err(|e| MigrateError::sql("Failed to begin transaction", e))?;
+        }
+
+        // Inside the transaction SET LOCAL ends with it; otherwise the setting is reset before
+        // the connection goes back to the pool
+        if let Some(timeout_ms) = options.statement_timeout_ms {
+            let set = if in_transaction { "SET LOCAL" } else { "SET" };
+            sqlx::query(&format!("{} statement_timeout = {}", set, timeout_ms))
+                .execute(&mut *target_conn)
+                .await
+                .map_err(|e| MigrateError::sql("Failed to set statement timeout", e))?;
+        }
+
+        let mut load_started = std::time::Instant::now();
+        let load_result = async {
+            let conn = &mut *target_conn;
+
+            // Truncate if needed (never when resuming, that would discard the rows already moved)
+            if options.truncate_before_insert && resume_from.is_none() {
+                emit_status("Truncating");
+                let truncate = format!("TRUNCATE TABLE {} CASCADE", target_full_table);
+                log.target(&truncate, None);
+                sqlx::query(&truncate)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| MigrateError::sql("Failed to truncate", e))?;
+            }
 
-        // Emit progress
+            // Nothing to copy; the target is still emptied above so it matches the source
+            if !has_rows {
+                return Ok((0, preflight_warnings));
+            }
+
+            // Disable constraints if needed
+            let mut warnings = preflight_warnings;
+            let triggers = if options.disable_constraints {
+                emit_status("Disabling triggers");
+                disable_triggers(conn, &target_full_table, in_transaction, &log).await
+            } else {
+                TriggerState::Enabled
+            };
+            if options.disable_constraints && triggers == TriggerState::Enabled {
+                warnings.push(
+                    "Triggers and foreign keys could not be disabled (requires table ownership or superuser); they fired during the load"
+                        .to_string(),
+                );
+            }
+
+            // Settings of the load connection, applied again if a retry has to replace it
+            let mut session = vec![set_search_path.clone()];
+            if triggers == TriggerState::ReplicationRole {
+                session.push("SET session_replication_role = replica".to_string());
+            }
+            if let Some(timeout_ms) = options.statement_timeout_ms {
+                session.push(format!("SET statement_timeout = {}", timeout_ms));
+            }
+
+            // Stream data in batches
+            let mut rows_transferred: i64 = 0;
+            let mut rows_rejected: i64 = 0;
+            let mut progress_throttle = ProgressThrottle::default();
+            let batch_size = options.batch_size as i64;
+            let insert_batch_size = options
+                .insert_batch_size
+                .unwrap_or(options.batch_size)
+                .max(1);
+
+            let mut source = SourceReader::open(
+                source_pool,
+                source_snapshot,
+                &log,
+                &source_full_table,
+                &select_list,
+                row_filter,
+                key_col.clone(),
+                resume_from.clone(),
+                batch_size,
+                options.max_retries,
+            )
+            .await?;
+
+            // With pipeline, the batch fetched while the previous one was inserted
+            let mut prefetched: Option<Vec<PgRow>> = None;
+            loop {
+                let rows = match prefetched.take() {
+                    Some(rows) => rows,
+                    None => source.next_batch(cancel_token).await?,
+                };
+                if rows.is_empty() {
+                    break;
+                }
+
+                let batch_count = rows.len() as i64;
+
+                // Build a single Multi-Row INSERT statement (Turbo Mode)
+                let mut row_values = Vec::new();
+                for row in &rows {
+                    let values = build_insert_values(row, &insert_columns, &transforms)?;
+                    row_values.push(format!("({})", values));
+                }
+                let insert_sql = |values: &[String]| {
+                    insert_statement(
+                        &target_full_table,
+                        &column_list,
+                        overriding,
+                        values,
+                        &on_conflict,
+                    )
+                };
+
+                // Key of the batch's last row, taken before a prefetch moves the reader past it
+                let batch_last_key = source.last_key_value().cloned();
+
+                let insert_batch = async {
+                    let mut batch_rejected: i64 = 0;
+                    // INSERT into TARGET, in chunks of insert_batch_size rows (fewer for wide rows)
+                    for range in insert_chunks(&row_values, insert_batch_size) {
+                        let first_row = range.start;
+                        let chunk = &row_values[range];
+                        let insert_query = insert_sql(chunk);
+                        log.target(&insert_sql(&["...".to_string()]), Some(chunk.len()));
+
+                        if options.isolate_failures {
+                            let chunk_rows = &rows[first_row..first_row + chunk.len()];
+                            let failures = insert_isolating_failures(
+                                conn,
+                                &insert_sql,
+                                chunk,
+                                chunk_rows,
+                                &key_columns,
+                                in_transaction,
+                            )
+                            .await?;
+                            for failure in failures {
+                                if rows_rejected < MAX_REPORTED_ROW_FAILURES as i64 {
+                                    warnings.push(failure);
+                                }
+                                rows_rejected += 1;
+                                batch_rejected += 1;
+                            }
+                        } else if in_transaction {
+                            // A failed statement aborts the transaction, so it can't be retried in
+                            // place
+                            sqlx::query(&insert_query)
+                                .execute(&mut *conn)
+                                .await
+                                .map_err(|e| MigrateError::sql("Turbo Insert failed", e))?;
+                        } else {
+                            // Stay on the load connection so its replication role, search_path
+                            // and statement timeout apply
+                            execute_with_retry(
+                                conn,
+                                target_pool,
+                                &session,
+                                &insert_query,
+                                options.max_retries,
+                                cancel_token,
+                            )
+                            .await
+                            .map_err(|e| MigrateError::sql("Turbo Insert failed", e))?;
+                        }
+                    }
+                    Ok::<i64, MigrateError>(batch_rejected)
+                };
+
+                // A short batch is the last one, so there is nothing to prefetch. If either side
+                // fails the other is dropped, and the batch was never checkpointed.
+                let batch_rejected = if options.pipeline && batch_count >= batch_size {
+                    let (batch_rejected, next) =
+                        tokio::try_join!(insert_batch, source.next_batch(cancel_token))?;
+                    prefetched = Some(next);
+                    batch_rejected
+                } else {
+                    insert_batch.await?
+                };
+
+                rows_transferred += batch_count - batch_rejected;
+
+                // Checkpoint the keyset position (rows are only durable outside a transaction)
+                if !in_transaction {
+                    if let (Some(checkpoint), Some(last_val)) = (checkpoint, &batch_last_key) {
+                        checkpoint.record_position(target_schema, target_table, last_val)?;
+                    }
+                }
+
+                if batch_count < batch_size {
+                    break;
+                }
+
+                // Emit progress
+                let (rows_per_second, eta_seconds) =
+                    throughput(rows_transferred, total_rows, load_started.elapsed());
+                let progress = MigrationProgress {
+                    table_name: table.to_string(),
+                    current_table,
+                    total_tables,
+                    rows_transferred,
+                    total_rows,
+                    status: "Migrating".to_string(),
+                    error: None,
+                    rows_per_second,
+                    eta_seconds,
+                };
+                if progress_throttle.due(rows_transferred, total_rows) {
+                    let _ = app_handle.emit("migration-progress", &progress);
+                }
+
+                // Wait out a pause between batches; the time paused doesn't count towards the rate
+                if pause_token.load(Ordering::Relaxed) {
+                    let _ = app_handle.emit(
+                        "migration-progress",
+                        &MigrationProgress {
+                            status: "Paused".to_string(),
+                            ..progress.clone()
+                        },
+                    );
+                    let paused = wait_while_paused(pause_token, cancel_token).await?;
+                    load_started += paused;
+                    let _ = app_handle.emit("migration-progress", &progress);
+                }
+
+                if let Some(max_rows_per_second) = options.max_rows_per_second {
+                    throttle(
+                        rows_transferred,
+                        max_rows_per_second,
+                        load_started,
+                        cancel_token,
+                    )
+                    .await?;
+                }
+            }
+
+            source.close().await;
+
+            // Re-enable constraints
+            match triggers {
+                TriggerState::Disabled => {
+                    let enable = format!("ALTER TABLE {} ENABLE TRIGGER ALL", target_full_table);
+                    log.target(&enable, None);
+                    sqlx::query(&enable)
+                        .execute(&mut *conn)
+                        .await
+                        .map_err(|e| MigrateError::sql("Failed to re-enable triggers", e))?;
+                }
+                TriggerState::ReplicationRole => {
+                    sqlx::query("RESET session_replication_role")
+                        .execute(&mut *conn)
+                        .await
+                        .map_err(|e| {
+                            MigrateError::sql("Failed to reset session_replication_role", e)
+                        })?;
+                }
+                TriggerState::Enabled => {}
+            }
+
+            if rows_rejected > MAX_REPORTED_ROW_FAILURES as i64 {
+                warnings.push(format!(
+                    "{} rows rejected in total, only the first {} are listed",
+                    rows_rejected, MAX_REPORTED_ROW_FAILURES
+                ));
+            }
+
+            Ok::<(i64, Vec<String>), MigrateError>((rows_transferred, warnings))
+        }
+        .await;
+
+        let (rows_transferred, warnings) = if in_shared_tx {
+            // Committed or rolled back by migrate_tables once every table is done
+            load_result?
+        } else if options.transactional {
+            match load_result {
+                Ok(loaded) => {
+                    sqlx::query("COMMIT")
+                        .execute(&mut *target_conn)
+                        .await
+                        .map_err(|e| MigrateError::sql("Failed to commit", e))?;
+                    loaded
+                }
+                Err(e) => {
+                    let _ = sqlx::query("ROLLBACK").execute(&mut *target_conn).await;
+                    return Err(e);
+                }
+            }
+        } else {
+            if options.statement_timeout_ms.is_some() {
+                let _ = sqlx::query("RESET statement_timeout")
+                    .execute(&mut *target_conn)
+                    .await;
+            }
+            // A failed load skips the reset above; don't return a replica-mode connection to the
+            // pool
+            if options.disable_constraints {
+                let _ = sqlx::query("RESET session_replication_role")
+                    .execute(&mut *target_conn)
+                    .await;
+            }
+            load_result?
+        };
+
+        // Recreate secondary indexes after the bulk load so inserts stay fast
+        if options.include_indexes && from_table {
+            create_indexes(
+                source_pool,
+                target_conn,
+                schema,
+                table,
+                target_schema,
+                target_table,
+                &log,
+            )
+            .await?;
+        }
+
+        // Sync sequences after migration (on TARGET)
+        let _ = sync_sequences(target_conn, target_schema, target_table, in_shared_tx, &log).await;
+
+        // Emit completion progress
+        let (rows_per_second, eta_seconds) =
+            throughput(rows_transferred, total_rows, load_started.elapsed());
         let progress = MigrationProgress {
             table_name: table.to_string(),
             current_table,
             total_tables,
             rows_transferred,
             total_rows,
-            status: "Migrating".to_string(),
+            status: "Complete".to_string(),
             error: None,
+            rows_per_second,
+            eta_seconds,
         };
         let _ = app_handle.emit("migration-progress", &progress);
 
-        if batch_count < batch_size {
+        Ok((rows_transferred, warnings))
+    }
+    .await;
+
+    // Also after a failure, so the connection doesn't go back to the pool with it changed
+    let _ = sqlx::query("RESET search_path")
+        .execute(&mut *target_conn)
+        .await;
+
+    result
+}
+
+/// Multi-row INSERT of already rendered `(...)` row values
+fn insert_statement(
+    target_full_table: &str,
+    column_list: &str,
+    overriding: &str,
+    values: &[String],
+    on_conflict: &str,
+) -> String {
+    format!(
+        "INSERT INTO {} ({}){} VALUES {}{}",
+        target_full_table,
+        column_list,
+        overriding,
+        values.join(", "),
+        on_conflict
+    )
+}
+
+/// Row ranges of `values` inserted per statement: at most `max_rows` rows, and split further
+/// so wide rows don't add up to more than `MAX_INSERT_BYTES`. A single larger row is inserted
+/// on its own.
+fn insert_chunks(values: &[String], max_rows: usize) -> Vec<std::ops::Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, value) in values.iter().enumerate() {
+        if i > start && (i - start == max_rows || bytes + value.len() > MAX_INSERT_BYTES) {
+            chunks.push(start..i);
+            start = i;
+            bytes = 0;
+        }
+        bytes += value.len();
+    }
+    if start < values.len() {
+        chunks.push(start..values.len());
+    }
+    chunks
+}
+
+/// Insert rows, splitting a failing INSERT in halves until the rows that fail are isolated.
+/// Returns one message per rejected row, identified by its primary key (or its position in
+/// the batch without one).
+async fn insert_isolating_failures(
+    conn: &mut PgConnection,
+    insert_sql: &impl Fn(&[String]) -> String,
+    values: &[String],
+    rows: &[PgRow],
+    primary_keys: &[ColumnInfo],
+    in_transaction: bool,
+) -> Result<Vec<String>, MigrateError> {
+    let mut failures = Vec::new();
+    // (start, end) ranges of `values` still to insert, first half on top so rows keep their
+    // order
+    let mut pending = vec![(0, values.len())];
+
+    while let Some((start, end)) = pending.pop() {
+        let result =
+            execute_savepointed(conn, &insert_sql(&values[start..end]), in_transaction).await;
+        match result {
+            Ok(()) => {}
+            Err(e) if end - start == 1 => {
+                let key = if primary_keys.is_empty() {
+                    format!("at batch position {}", start + 1)
+                } else {
+                    let names: Vec<&str> = primary_keys.iter().map(|c| c.name.as_str()).collect();
+                    let key_values = primary_keys
+                        .iter()
+                        .map(|pk| get_column_value_as_sql(&rows[start], &pk.name, &pk.data_type))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(|v| v.join(", "))
+                        .unwrap_or_else(|_| "?".to_string());
+                    format!("({})=({})", names.join(", "), key_values)
+                };
+                failures.push(format!("row {} rejected: {}", key, e));
+            }
+            // Lost connections and timeouts would fail every half as well
+            Err(e) if is_transient_error(&e) => {
+                return Err(MigrateError::sql("Turbo Insert failed", e));
+            }
+            Err(_) => {
+                let middle = start + (end - start) / 2;
+                pending.push((middle, end));
+                pending.push((start, middle));
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Create the source table's secondary indexes on the target table
+async fn create_indexes(
+    source_pool: &PgPool,
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    target_schema: &str,
+    target_table: &str,
+    log: &StatementLog<'_>,
+) -> Result<(), MigrateError> {
+    let indexes = get_table_indexes(source_pool, schema, table).await?;
+    for index in &indexes {
+        let index = IndexInfo {
+            name: renamed_for_target(&index.name, table, target_table),
+            ..index.clone()
+        };
+        let create_index = generate_create_index_statement(&index, target_schema, target_table);
+        log.target(&create_index, None);
+        sqlx::query(&create_index)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| MigrateError::sql(&format!("Failed to create index {}", index.name), e))?;
+    }
+    Ok(())
+}
+
+/// Name for an index or partition of `table` when the table is renamed to `target_table`, so
+/// it doesn't collide with the source's own objects in the same schema: `users_email_idx`
+/// becomes `users_backup_email_idx`, other names get the target table as a prefix. Only a
+/// whole-word prefix is replaced, so `users_email_idx` of table `user` isn't split mid-word.
+fn renamed_for_target(name: &str, table: &str, target_table: &str) -> String {
+    let own_prefix = name
+        .strip_prefix(table)
+        .filter(|rest| rest.is_empty() || rest.starts_with('_'));
+    if table == target_table {
+        name.to_string()
+    } else if let Some(rest) = own_prefix {
+        format!("{}{}", target_table, rest)
+    } else {
+        format!("{}_{}", target_table, name)
+    }
+}
+
+/// Limits batch progress events to one per `PROGRESS_INTERVAL_MS` or whole percent of the
+/// table, so small batches on a big table don't flood the event bus. Reaching the total row
+/// count is always emitted, so the table's progress ends at 100%.
+#[derive(Default)]
+struct ProgressThrottle {
+    last_emit: Option<std::time::Instant>,
+    last_percent: i64,
+}
+
+impl ProgressThrottle {
+    /// Whether progress at `rows` of `total_rows` is due to be emitted, recording it if so
+    fn due(&mut self, rows: i64, total_rows: i64) -> bool {
+        let percent = if total_rows > 0 {
+            rows * 100 / total_rows
+        } else {
+            0
+        };
+        let complete = total_rows > 0 && rows >= total_rows;
+        let due = complete
+            || percent > self.last_percent
+            || self.last_emit.is_none_or(|last| {
+                last.elapsed() >= std::time::Duration::from_millis(PROGRESS_INTERVAL_MS)
+            });
+        if due {
+            self.last_emit = Some(std::time::Instant::now());
+            self.last_percent = percent;
+        }
+        due
+    }
+}
+
+/// Sleep until `rows_transferred` is within `max_rows_per_second` of the load's start,
+/// waking up regularly to notice a cancellation
+async fn throttle(
+    rows_transferred: i64,
+    max_rows_per_second: u64,
+    started: std::time::Instant,
+    cancel_token: &CancellationToken,
+) -> Result<(), MigrateError> {
+    let target = std::time::Duration::from_secs_f64(
+        rows_transferred as f64 / max_rows_per_second.max(1) as f64,
+    );
+    while let Some(remaining) = target.checked_sub(started.elapsed()) {
+        if remaining.is_zero() {
             break;
         }
+        if cancel_token.load(Ordering::Relaxed) {
+            return Err(MigrateError::Cancelled);
+        }
+        tokio::time::sleep(remaining.min(std::time::Duration::from_millis(THROTTLE_POLL_MS))).await;
     }
+    Ok(())
+}
 
-    // Re-enable constraints
-    if options.disable_constraints {
-        let _ = sqlx::query(&format!("ALTER TABLE {} ENABLE TRIGGER ALL", target_full_table))
-            .execute(target_pool)
+/// Sleep while the migration is paused, returning how long that was
+async fn wait_while_paused(
+    pause_token: &PauseToken,
+    cancel_token: &CancellationToken,
+) -> Result<std::time::Duration, MigrateError> {
+    let started = std::time::Instant::now();
+    while pause_token.load(Ordering::Relaxed) {
+        if cancel_token.load(Ordering::Relaxed) {
+            return Err(MigrateError::Cancelled);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(THROTTLE_POLL_MS)).await;
+    }
+    Ok(started.elapsed())
+}
+
+/// Compute rows per second and the estimated seconds remaining.
+/// The ETA is `None` when the total row count is unknown (zero).
+fn throughput(
+    rows_transferred: i64,
+    total_rows: i64,
+    elapsed: std::time::Duration,
+) -> (f64, Option<u64>) {
+    let secs = elapsed.as_secs_f64();
+    let rows_per_second = if secs > 0.0 {
+        rows_transferred as f64 / secs
+    } else {
+        0.0
+    };
+
+    let eta_seconds = if total_rows <= 0 {
+        None
+    } else if rows_transferred >= total_rows {
+        Some(0)
+    } else if rows_per_second > 0.0 {
+        Some(((total_rows - rows_transferred) as f64 / rows_per_second).ceil() as u64)
+    } else {
+        None
+    };
+
+    (rows_per_second, eta_seconds)
+}
+
+/// Run a database operation, retrying transient failures (dropped connections, timeouts)
+/// with exponential backoff. Other errors such as syntax or constraint violations fail
+/// immediately.
+async fn with_retry<T, F, Fut>(
+    max_retries: usize,
+    cancel_token: &CancellationToken,
+    mut operation: F,
+) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Err(e)
+                if attempt < max_retries
+                    && is_transient_error(&e)
+                    && !cancel_token.load(Ordering::Relaxed) =>
+            {
+                let delay = RETRY_BASE_DELAY_MS << attempt.min(6);
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Like `with_retry`, for a statement on the load connection. A transient error means that
+/// connection is gone, so before each retry it's replaced in place by a fresh one from the
+/// pool, with the load's `session` settings applied again.
+async fn execute_with_retry(
+    conn: &mut PgConnection,
+    pool: &PgPool,
+    session: &[String],
+    sql: &str,
+    max_retries: usize,
+    cancel_token: &CancellationToken,
+) -> Result<(), sqlx::Error> {
+    let mut attempt = 0;
+    loop {
+        match sqlx::query(sql).execute(&mut *conn).await {
+            Err(e)
+                if attempt < max_retries
+                    && is_transient_error(&e)
+                    && !cancel_token.load(Ordering::Relaxed) =>
+            {
+                let delay = RETRY_BASE_DELAY_MS << attempt.min(6);
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                attempt += 1;
+
+                // If reconnecting fails too, the next attempt fails on the old connection
+                // and counts as a retry
+                if let Ok(mut fresh) = reconnect(pool, session).await {
+                    std::mem::swap(conn, &mut *fresh);
+                    // `fresh` now holds the dead connection; don't return it to the pool
+                    fresh.close_on_drop();
+                }
+            }
+            result => return result.map(|_| ()),
+        }
+    }
+}
+
+/// A new pooled connection with `session` statements run on it
+async fn reconnect(
+    pool: &PgPool,
+    session: &[String],
+) -> Result<sqlx::pool::PoolConnection<sqlx::Postgres>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    for statement in session {
+        sqlx::query(statement).execute(&mut *conn).await?;
+    }
+    Ok(conn)
+}
+
+/// Whether an error is likely to succeed if retried
+fn is_transient_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(db_error) => db_error.code().is_some_and(|c| is_transient_code(&c)),
+        _ => false,
+    }
+}
+
+/// Whether a SQLSTATE is worth retrying: the connection_exception class, server shutdown or
+/// startup (57P01-57P03), too many connections (53300), and serialization failures and
+/// deadlocks (40001, 40P01). Statements are only retried outside a transaction, where a
+/// serialization failure or deadlock has rolled back just that statement, so running it
+/// again is safe.
+fn is_transient_code(code: &str) -> bool {
+    code.starts_with("08")
+        || matches!(
+            code,
+            "57P01" | "57P02" | "57P03" | "53300" | "40001" | "40P01"
+        )
+}
+
+/// Build the ON CONFLICT clause appended to each INSERT
+fn conflict_clause(
+    mode: ConflictMode,
+    conflict_target: Option<&[String]>,
+    table_schema: &TableSchema,
+    selection: &TableSelection,
+) -> Result<String, MigrateError> {
+    match (mode, conflict_target) {
+        (ConflictMode::Error, _) => Ok(String::new()),
+        (ConflictMode::DoNothing, None) => Ok(" ON CONFLICT DO NOTHING".to_string()),
+        (ConflictMode::DoNothing, Some(target)) => {
+            let target_cols: Vec<String> = target.iter().map(|c| quote_ident(c)).collect();
+            Ok(format!(
+                " ON CONFLICT ({}) DO NOTHING",
+                target_cols.join(", ")
+            ))
+        }
+        (ConflictMode::Upsert, target) => {
+            // Conflict target columns, named as on the target table
+            let target_cols: Vec<String> = match target {
+                Some(target) => target.to_vec(),
+                None => {
+                    if table_schema.primary_key_columns.is_empty() {
+                        return Err(MigrateError::Other(
+                            "Upsert conflict mode requires a primary key".to_string(),
+                        ));
+                    }
+                    table_schema
+                        .primary_key_columns
+                        .iter()
+                        .map(|c| selection.target_column(c).to_string())
+                        .collect()
+                }
+            };
+
+            let updates: Vec<String> = table_schema
+                .columns
+                .iter()
+                .filter(|c| c.generation_expression.is_none())
+                .filter(|c| !selection.exclude_columns.contains(&c.name))
+                .map(|c| selection.target_column(&c.name))
+                .filter(|name| !target_cols.iter().any(|t| t == name))
+                .map(|name| {
+                    let name = quote_ident(name);
+                    format!("{} = EXCLUDED.{}", name, name)
+                })
+                .collect();
+
+            let target_list: Vec<String> = target_cols.iter().map(|c| quote_ident(c)).collect();
+            if updates.is_empty() {
+                Ok(format!(
+                    " ON CONFLICT ({}) DO NOTHING",
+                    target_list.join(", ")
+                ))
+            } else {
+                Ok(format!(
+                    " ON CONFLICT ({}) DO UPDATE SET {}",
+                    target_list.join(", "),
+                    updates.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+/// How triggers were suppressed for the duration of a load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TriggerState {
+    Enabled,
+    Disabled,        // ALTER TABLE ... DISABLE TRIGGER ALL
+    ReplicationRole, // session_replication_role = replica
+}
+
+/// Disable all triggers on a table. Without table ownership this falls back to
+/// `session_replication_role = replica` (allowed for e.g. rds_superuser on managed Postgres).
+/// Inside a transaction a permission failure doesn't abort the surrounding transaction.
+async fn disable_triggers(
+    conn: &mut PgConnection,
+    full_table: &str,
+    in_transaction: bool,
+    log: &StatementLog<'_>,
+) -> TriggerState {
+    let attempts = [
+        (
+            format!("ALTER TABLE {} DISABLE TRIGGER ALL", full_table),
+            TriggerState::Disabled,
+        ),
+        (
+            "SET session_replication_role = replica".to_string(),
+            TriggerState::ReplicationRole,
+        ),
+    ];
+
+    for (sql, state) in attempts {
+        log.target(&sql, None);
+        if execute_tolerant(conn, &sql, in_transaction).await {
+            return state;
+        }
+    }
+
+    TriggerState::Enabled
+}
+
+/// Run a statement whose failure is tolerated, returning whether it succeeded. Inside a
+/// transaction it's wrapped in a savepoint so that a failure doesn't abort the transaction.
+async fn execute_tolerant(conn: &mut PgConnection, sql: &str, in_transaction: bool) -> bool {
+    execute_savepointed(conn, sql, in_transaction).await.is_ok()
+}
+
+/// Source columns missing from an existing target table. Fails if a column the target has is of
+/// another type, or if a missing column couldn't be added to a table that has rows because it
+/// is NOT NULL without a default.
+fn missing_target_columns<'a>(
+    source_columns: &'a [ColumnInfo],
+    target: &TableSchema,
+) -> Result<Vec<&'a ColumnInfo>, MigrateError> {
+    let mut missing = Vec::new();
+    let mut problems = Vec::new();
+    for col in source_columns {
+        match target.columns.iter().find(|c| c.name == col.name) {
+            Some(target_col) if target_col.data_type != col.data_type => problems.push(format!(
+                "column {} is {} in the source but {} in the target",
+                col.name, col.data_type, target_col.data_type
+            )),
+            Some(_) => {}
+            None if !col.is_nullable
+                && col.column_default.is_none()
+                && col.identity_generation.is_none()
+                && col.generation_expression.is_none() =>
+            {
+                problems.push(format!(
+                    "column {} is missing in the target and can't be added because it is NOT NULL without a default",
+                    col.name
+                ))
+            }
+            None => missing.push(col),
+        }
+    }
+    if !problems.is_empty() {
+        return Err(MigrateError::Other(format!(
+            "Target table {}.{} doesn't match the source: {}",
+            target.schema_name,
+            target.table_name,
+            problems.join("; ")
+        )));
+    }
+    Ok(missing)
+}
+
+/// Run a statement whose failure the caller handles, inside a savepoint when in a transaction
+async fn execute_savepointed(
+    conn: &mut PgConnection,
+    sql: &str,
+    in_transaction: bool,
+) -> Result<(), sqlx::Error> {
+    if in_transaction {
+        let _ = sqlx::query("SAVEPOINT pgm_tolerant")
+            .execute(&mut *conn)
             .await;
     }
 
-    // Sync sequences after migration (on TARGET)
-    let _ = sync_sequences(target_pool, target_schema, table).await;
+    let result = sqlx::query(sql).execute(&mut *conn).await.map(|_| ());
+
+    if in_transaction {
+        let release = if result.is_ok() {
+            "RELEASE SAVEPOINT pgm_tolerant"
+        } else {
+            "ROLLBACK TO SAVEPOINT pgm_tolerant"
+        };
+        let _ = sqlx::query(release).execute(&mut *conn).await;
+    }
+
+    result
+}
+
+/// Whether CREATE TABLE copies the column's default as written, rather than turning it into
+/// SERIAL, an identity or a generated column
+fn has_verbatim_default(col: &ColumnInfo, preserve_sequences: bool) -> bool {
+    let is_serial = !preserve_sequences
+        && col
+            .column_default
+            .as_deref()
+            .is_some_and(|d| d.contains("nextval"))
+        && matches!(
+            col.formatted_type.to_lowercase().as_str(),
+            "integer" | "bigint" | "smallint"
+        );
+    col.column_default.is_some()
+        && col.generation_expression.is_none()
+        && col.identity_generation.is_none()
+        && !is_serial
+}
+
+/// Create standalone sequences of a schema on the target and set them to the source's value
+pub async fn migrate_sequences(
+    source_pool: &PgPool,
+    target_pool: &PgPool,
+    schema: &str,
+    target_schema: &str,
+) -> Result<usize, MigrateError> {
+    let sequences = get_standalone_sequences(source_pool, schema).await?;
+    let target_version = server_version_num(target_pool).await?;
+
+    for sequence in &sequences {
+        let create_sequence =
+            generate_create_sequence_statement(sequence, target_schema, target_version);
+        sqlx::query(&create_sequence)
+            .execute(target_pool)
+            .await
+            .map_err(|e| {
+                MigrateError::sql(&format!("Failed to create sequence {}", sequence.name), e)
+            })?;
+
+        // An unused sequence restarts at its start value; otherwise continue after last_value
+        let (value, is_called) = match sequence.last_value {
+            Some(last_value) => (last_value, true),
+            None => (sequence.start_value, false),
+        };
+        sqlx::query("SELECT setval($1::regclass, $2, $3)")
+            .bind(format!(
+                "{}.{}",
+                quote_ident(target_schema),
+                quote_ident(&sequence.name)
+            ))
+            .bind(value)
+            .bind(is_called)
+            .execute(target_pool)
+            .await
+            .map_err(|e| {
+                MigrateError::sql(&format!("Failed to set sequence {}", sequence.name), e)
+            })?;
+    }
+
+    Ok(sequences.len())
+}
+
+/// Reset sequences to max value + 1. A sequence that can't be set is skipped; inside a
+/// transaction that doesn't abort it.
+async fn sync_sequences(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    in_transaction: bool,
+    log: &StatementLog<'_>,
+) -> Result<(), MigrateError> {
+    // Parameters can't be bound inside a DO block, so look the sequences up first
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            quote_ident(sn.nspname) || '.' || quote_ident(s.relname) AS seq_fqn,
+            quote_ident(a.attname) AS col_name,
+            quote_ident(tn.nspname) || '.' || quote_ident(t.relname) AS table_fqn
+        FROM pg_class s
+        JOIN pg_namespace sn ON sn.oid = s.relnamespace
+        JOIN pg_depend d ON d.objid = s.oid AND d.deptype IN ('a', 'i')
+        JOIN pg_class t ON t.oid = d.refobjid
+        JOIN pg_namespace tn ON tn.oid = t.relnamespace
+        JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = d.refobjsubid
+        WHERE s.relkind = 'S'
+        AND tn.nspname = $1
+        AND t.relname = $2
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to sync sequences", e))?;
+
+    for row in rows {
+        let seq_fqn: String = row.get("seq_fqn");
+        let col_name: String = row.get("col_name");
+        let table_fqn: String = row.get("table_fqn");
+        let query = format!(
+            "SELECT setval({}, COALESCE((SELECT MAX({}) FROM {}), 0) + 1, false)",
+            quote_literal(&seq_fqn),
+            col_name,
+            table_fqn
+        );
+        log.target(&query, None);
+        execute_tolerant(conn, &query, in_transaction).await;
+    }
+
+    Ok(())
+}
+
+/// Build the SELECT expression for a column
+fn select_column_expr(col: &super::schema::ColumnInfo) -> String {
+    if col.data_type == "ARRAY"
+        || col.data_type == "USER-DEFINED"
+        || RANGE_TYPES.contains(&col.data_type.as_str())
+        || TEXT_CAST_TYPES.contains(&col.data_type.as_str())
+    {
+        // Read arrays, ranges, mac addresses, bit strings, XML, full-text search types and
+        // user-defined types (enums, hstore, pgvector, ...) in their text form, which is a valid
+        // input literal; array and hstore text output already escape quotes and NULLs
+        format!(
+            "{}::text AS {}",
+            quote_ident(&col.name),
+            quote_ident(&col.name)
+        )
+    } else if col.data_type == "money" {
+        // Read money as numeric to avoid depending on the server's lc_monetary formatting
+        format!(
+            "{}::numeric AS {}",
+            quote_ident(&col.name),
+            quote_ident(&col.name)
+        )
+    } else {
+        quote_ident(&col.name)
+    }
+}
+
+/// Built-in range types, which are copied through their text representation
+const RANGE_TYPES: [&str; 6] = [
+    "int4range",
+    "int8range",
+    "numrange",
+    "tsrange",
+    "tstzrange",
+    "daterange",
+];
+
+/// Types without a native sqlx decoder here, copied through their text representation.
+/// sqlx has no decoder (or feature) for `xml` or the full-text search types at all, so they
+/// have to be cast.
+const TEXT_CAST_TYPES: [&str; 7] = [
+    "macaddr",
+    "macaddr8",
+    "bit",
+    "bit varying",
+    "xml",
+    "tsvector",
+    "tsquery",
+];
+
+/// Types read by a dedicated branch of `get_column_value_as_sql` or its string fallback
+const NATIVE_TYPES: [&str; 35] = [
+    "integer",
+    "int4",
+    "bigint",
+    "int8",
+    "smallint",
+    "int2",
+    "oid",
+    "numeric",
+    "decimal",
+    "money",
+    "real",
+    "float4",
+    "double precision",
+    "float8",
+    "boolean",
+    "bool",
+    "timestamp",
+    "timestamp without time zone",
+    "timestamp with time zone",
+    "timestamptz",
+    "date",
+    "time",
+    "time without time zone",
+    "time with time zone",
+    "timetz",
+    "interval",
+    "inet",
+    "cidr",
+    "json",
+    "jsonb",
+    "bytea",
+    "text",
+    "character varying",
+    "character",
+    "name",
+];
+
+/// Whether the serializer can read a column of this `data_type`
+pub fn is_supported_type(data_type: &str) -> bool {
+    let dt = data_type.to_lowercase();
+    dt == "array"
+        || dt == "user-defined"
+        || NATIVE_TYPES.contains(&dt.as_str())
+        || RANGE_TYPES.contains(&dt.as_str())
+        || TEXT_CAST_TYPES.contains(&dt.as_str())
+}
+
+/// Most rows returned by `preview_table`
+const MAX_PREVIEW_ROWS: usize = 100;
+
+/// Read up to `limit` rows of a table, rendering each value the way it would be migrated
+pub async fn preview_table(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    limit: usize,
+) -> Result<TablePreview, MigrateError> {
+    let table_schema = get_table_schema(pool, schema, table).await?;
+    let (columns, select_list) = rendered_columns(table_schema.columns);
+
+    let query = format!(
+        "SELECT {} FROM {}.{} LIMIT {}",
+        select_list,
+        quote_ident(schema),
+        quote_ident(table),
+        limit.clamp(1, MAX_PREVIEW_ROWS)
+    );
+    let rows = sqlx::query(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to preview table", e))?;
+
+    Ok(TablePreview {
+        rows: rows
+            .iter()
+            .map(|row| render_row(row, &columns, &[]))
+            .collect::<Result<_, _>>()?,
+        columns: columns.into_iter().map(|c| c.name).collect(),
+    })
+}
+
+/// Most values returned by `get_column_distinct_values`
+const MAX_DISTINCT_VALUES: usize = 1000;
+
+/// Up to `limit` distinct values of a column in sort order, rendered the way they would be
+/// migrated. One extra value is read to tell whether there are more.
+pub async fn get_column_distinct_values(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    column: &str,
+    limit: usize,
+) -> Result<DistinctValues, MigrateError> {
+    let table_schema = get_table_schema(pool, schema, table).await?;
+    let col = table_schema
+        .columns
+        .into_iter()
+        .find(|c| c.name == column)
+        .ok_or_else(|| {
+            MigrateError::Other(format!(
+                "Column {} does not exist in {}.{}",
+                column, schema, table
+            ))
+        })?;
+    let (columns, select_list) = rendered_columns(vec![col]);
+
+    let limit = limit.clamp(1, MAX_DISTINCT_VALUES);
+    let query = format!(
+        "SELECT DISTINCT {} FROM {}.{} ORDER BY 1 LIMIT {}",
+        select_list,
+        quote_ident(schema),
+        quote_ident(table),
+        limit + 1
+    );
+    let rows = sqlx::query(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to read distinct values", e))?;
+
+    let truncated = rows.len() > limit;
+    let values = rows
+        .iter()
+        .take(limit)
+        .map(|row| render_row(row, &columns, &[]).map(|mut v| v.remove(0)))
+        .collect::<Result<_, _>>()?;
+    Ok(DistinctValues { values, truncated })
+}
+
+/// Time of day with the microsecond resolution PostgreSQL stores. `%.f` would pick 3, 6 or 9
+/// digits depending on the value; whole seconds get no fraction at all.
+fn time_literal(time: chrono::NaiveTime) -> String {
+    if time.nanosecond() == 0 {
+        time.format("%H:%M:%S").to_string()
+    } else {
+        time.format("%H:%M:%S%.6f").to_string()
+    }
+}
+
+/// `oid` literal; rendered unsigned, since values above `i32::MAX` are valid OIDs
+fn oid_literal(oid: sqlx::postgres::types::Oid) -> String {
+    oid.0.to_string()
+}
+
+/// Quoted `timestamp` literal
+fn timestamp_literal(value: chrono::NaiveDateTime) -> String {
+    format!(
+        "'{} {}'",
+        value.format("%Y-%m-%d"),
+        time_literal(value.time())
+    )
+}
+
+/// Quoted `timestamptz` literal, in UTC
+fn timestamptz_literal(value: chrono::DateTime<chrono::Utc>) -> String {
+    format!(
+        "'{} {}+00'",
+        value.format("%Y-%m-%d"),
+        time_literal(value.time())
+    )
+}
+
+/// Quoted `timetz` literal, keeping the stored offset
+fn timetz_literal(
+    value: &sqlx::postgres::types::PgTimeTz<chrono::NaiveTime, chrono::FixedOffset>,
+) -> String {
+    format!("'{}{}'", time_literal(value.time), value.offset)
+}
+
+/// Quoted `interval` literal. Months, days and microseconds are kept apart, as PostgreSQL
+/// stores them, so '1 mon' doesn't turn into '30 days'.
+fn interval_literal(value: &sqlx::postgres::types::PgInterval) -> String {
+    format!(
+        "'{} months {} days {} microseconds'",
+        value.months, value.days, value.microseconds
+    )
+}
+
+/// Quoted `money` literal (read as numeric), so the target parses it with money input rules
+fn money_literal(value: &bigdecimal::BigDecimal) -> String {
+    quote_literal(&value.to_string())
+}
+
+/// `bit`/`bit varying` literal from its text form, e.g. B'1010'
+fn bit_literal(bits: &str) -> String {
+    format!("B{}", quote_literal(bits))
+}
+
+/// `bytea` literal in hex escape format, e.g. '\x48656c6c6f'
+fn bytea_literal(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("'\\x{}'", hex)
+}
+
+/// Columns as read for rendering, and their select list. Types the serializer can't read
+/// are selected through their text form.
+fn rendered_columns(columns: Vec<ColumnInfo>) -> (Vec<ColumnInfo>, String) {
+    let columns: Vec<ColumnInfo> = columns
+        .into_iter()
+        .map(|col| {
+            if is_supported_type(&col.data_type) {
+                col
+            } else {
+                ColumnInfo {
+                    data_type: "text".to_string(),
+                    ..col
+                }
+            }
+        })
+        .collect();
+    let select_list: Vec<String> = columns
+        .iter()
+        .map(|col| {
+            if col.data_type == "text" {
+                format!(
+                    "{}::text AS {}",
+                    quote_ident(&col.name),
+                    quote_ident(&col.name)
+                )
+            } else {
+                select_column_expr(col)
+            }
+        })
+        .collect();
+    (columns, select_list.join(", "))
+}
+
+/// A row's values as text (`None` for NULL), rendered the way they would be migrated
+fn render_row(
+    row: &PgRow,
+    columns: &[ColumnInfo],
+    transforms: &[Option<CompiledTransform>],
+) -> Result<Vec<Option<String>>, MigrateError> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            let value = get_column_value_as_sql(row, &col.name, &col.data_type)?;
+            Ok(literal_to_text(transformed(value, transforms.get(i))))
+        })
+        .collect()
+}
 
-    // Emit completion progress
-    let progress = MigrationProgress {
-        table_name: table.to_string(),
-        current_table,
-        total_tables,
-        rows_transferred,
-        total_rows,
-        status: "Complete".to_string(),
-        error: None,
-    };
-    let _ = app_handle.emit("migration-progress", &progress);
-
-    Ok(rows_transferred)
-}
-
-/// Reset sequences to max value + 1
-async fn sync_sequences(pool: &PgPool, schema: &str, table: &str) -> Result<(), String> {
-    let query = r#"
-        DO $$
-        DECLARE
-            r RECORD;
-            max_val bigint;
-        BEGIN
-            FOR r IN (
-                SELECT 
-                    quote_ident(n.nspname) || '.' || quote_ident(s.relname) AS seq_fqn,
-                    quote_ident(a.attname) AS col_name,
-                    quote_ident(n.nspname) || '.' || quote_ident(t.relname) AS table_fqn
-                FROM pg_class s
-                JOIN pg_namespace n ON n.oid = s.relnamespace
-                JOIN pg_depend d ON d.objid = s.oid AND d.deptype = 'a'
-                JOIN pg_class t ON t.oid = d.refobjid
-                JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = d.refobjsubid
-                WHERE s.relkind = 'S'
-                AND n.nspname = $1
-                AND t.relname = $2
-            ) LOOP
-                EXECUTE format('SELECT setval(%L, COALESCE((SELECT MAX(%s) FROM %s), 0) + 1, false)', 
-                    r.seq_fqn, r.col_name, r.table_fqn);
-            END LOOP;
-        END $$;
-    "#;
-
-    sqlx::query(query)
-        .bind(schema)
-        .bind(table)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to sync sequences: {}", e))?;
+/// Undo the quoting of a serialized value: `NULL` becomes `None` and `'it''s'` becomes `it's`
+fn literal_to_text(literal: String) -> Option<String> {
+    if literal == "NULL" {
+        return None;
+    }
+    if let Some(inner) = literal
+        .strip_prefix("E'")
+        .and_then(|l| l.strip_suffix('\''))
+    {
+        return Some(unescape_string(inner));
+    }
+    let quoted = literal
+        .strip_prefix("B'")
+        .or_else(|| literal.strip_prefix('\''))
+        .and_then(|l| l.strip_suffix('\''));
+    match quoted {
+        Some(inner) => Some(inner.replace("''", "'")),
+        None => Some(literal),
+    }
+}
 
-    Ok(())
+/// Body of an `E'...'` literal as text: backslash escapes and doubled quotes resolved
+fn unescape_string(inner: &str) -> String {
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => text.push('\n'),
+                Some('t') => text.push('\t'),
+                Some('r') => text.push('\r'),
+                Some('b') => text.push('\u{8}'),
+                Some('f') => text.push('\u{c}'),
+                Some(other) => text.push(other),
+                None => {}
+            },
+            '\'' => {
+                // Doubled quote
+                chars.next();
+                text.push('\'');
+            }
+            c => text.push(c),
+        }
+    }
+    text
 }
 
 /// Build insert values from a row
-fn build_insert_values(row: &PgRow, columns: &[super::schema::ColumnInfo]) -> Result<String, String> {
+fn build_insert_values(
+    row: &PgRow,
+    columns: &[super::schema::ColumnInfo],
+    transforms: &[Option<CompiledTransform>],
+) -> Result<String, MigrateError> {
     let mut values = Vec::new();
 
-    for col in columns {
+    for (i, col) in columns.iter().enumerate() {
         let value = get_column_value_as_sql(row, &col.name, &col.data_type)?;
-        values.push(value);
+        values.push(transformed(value, transforms.get(i)));
     }
 
     Ok(values.join(", "))
 }
 
+/// A serialized value after its column's transform, if it has one
+fn transformed(literal: String, transform: Option<&Option<CompiledTransform>>) -> String {
+    match transform {
+        Some(Some(transform)) => transform.apply(literal),
+        _ => literal,
+    }
+}
+
+/// Error for a column value that couldn't be decoded as `as_type`
+fn decode_error(column: &str, as_type: &str, e: sqlx::Error) -> MigrateError {
+    MigrateError::sql(&format!("Col {} as {} failed", column, as_type), e)
+}
+
 /// Get column value as SQL string
-fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result<String, String> {
+fn get_column_value_as_sql(
+    row: &PgRow,
+    column: &str,
+    data_type: &str,
+) -> Result<String, MigrateError> {
     let dt = data_type.to_lowercase();
     
     // Handle Numeric Types
@@ -375,7 +4505,7 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(v.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as i32 failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "i32", e))
         };
     }
     
@@ -384,7 +4514,7 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(v.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as i64 failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "i64", e))
         };
     }
     
@@ -393,7 +4523,17 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(v.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as i16 failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "i16", e))
+        };
+    }
+
+    // Handle OID columns (unsigned 32-bit, so they don't fit an i32)
+    if dt == "oid" {
+        let val: Result<Option<sqlx::postgres::types::Oid>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(oid_literal(v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "oid", e))
         };
     }
 
@@ -402,7 +4542,16 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(v.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as numeric failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "numeric", e))
+        };
+    }
+
+    if dt == "money" {
+        let val: Result<Option<bigdecimal::BigDecimal>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(money_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "money", e))
         };
     }
 
@@ -411,7 +4560,7 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(v.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as f32 failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "f32", e))
         };
     }
 
@@ -420,7 +4569,7 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(v.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as f64 failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "f64", e))
         };
     }
     
@@ -429,7 +4578,7 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(if v { "TRUE" } else { "FALSE" }.to_string()),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as bool failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "bool", e))
         };
     }
 
@@ -437,18 +4586,18 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
     if dt == "timestamp" || dt == "timestamp without time zone" {
         let val: Result<Option<chrono::NaiveDateTime>, _> = row.try_get(column);
         return match val {
-            Ok(Some(v)) => Ok(format!("'{}'", v.format("%Y-%m-%d %H:%M:%S%.f"))),
+            Ok(Some(v)) => Ok(timestamp_literal(v)),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as timestamp failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "timestamp", e))
         };
     }
 
     if dt == "timestamp with time zone" || dt == "timestamptz" {
         let val: Result<Option<chrono::DateTime<chrono::Utc>>, _> = row.try_get(column);
         return match val {
-            Ok(Some(v)) => Ok(format!("'{}'", v.to_rfc3339())),
+            Ok(Some(v)) => Ok(timestamptz_literal(v)),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as timestamptz failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "timestamptz", e))
         };
     }
 
@@ -457,16 +4606,37 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(format!("'{}'", v.format("%Y-%m-%d"))),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as date failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "date", e))
         };
     }
 
     if dt == "time" || dt == "time without time zone" {
         let val: Result<Option<chrono::NaiveTime>, _> = row.try_get(column);
         return match val {
-            Ok(Some(v)) => Ok(format!("'{}'", v.format("%H:%M:%S%.f"))),
+            Ok(Some(v)) => Ok(format!("'{}'", time_literal(v))),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as time failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "time", e))
+        };
+    }
+
+    if dt == "time with time zone" || dt == "timetz" {
+        let val: Result<
+            Option<sqlx::postgres::types::PgTimeTz<chrono::NaiveTime, chrono::FixedOffset>>,
+            _,
+        > = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(timetz_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "timetz", e))
+        };
+    }
+
+    if dt == "interval" {
+        let val: Result<Option<sqlx::postgres::types::PgInterval>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(interval_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "interval", e))
         };
     }
 
@@ -476,7 +4646,47 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(format!("'{}'", v)),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as inet failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "inet", e))
+        };
+    }
+
+    if dt == "macaddr" || dt == "macaddr8" {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(format!("'{}'", v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "macaddr", e))
+        };
+    }
+
+    // Handle Bit String Types (selected as text, e.g. '1010')
+    if dt == "bit" || dt == "bit varying" {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(bit_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "bit", e))
+        };
+    }
+
+    // Handle XML (selected as text; documents and fragments often contain quotes)
+    if dt == "xml" {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(quote_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "xml", e))
+        };
+    }
+
+    // Handle Full-Text Search Types (selected as text, e.g. 'a':1 'cat':2 or 'fat' & 'rat';
+    // lexemes are quoted, so the quotes are doubled)
+    if dt == "tsvector" || dt == "tsquery" {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(quote_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, &dt, e))
         };
     }
 
@@ -486,14 +4696,55 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
         return match val {
             Ok(Some(v)) => Ok(format!("'{}'", v.to_string().replace('\'', "''"))),
             Ok(None) => Ok("NULL".to_string()),
-            Err(e) => Err(format!("Col {} as json failed: {}", column, e))
+            Err(e) => Err(decode_error(column, "json", e))
+        };
+    }
+
+    // Handle Array Types (selected as text, e.g. '{1,2,3}' or '{{"a,b",NULL}}')
+    if dt == "array" {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(quote_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "array", e))
+        };
+    }
+
+    // Handle Range Types (selected as text, e.g. '[1,10)' or 'empty')
+    if RANGE_TYPES.contains(&dt.as_str()) {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(quote_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "range", e))
+        };
+    }
+
+    // Handle User-Defined Types such as enums, composites or pgvector's vector (selected as
+    // text, e.g. '[1,2,3]' or '(1,"a b")')
+    if dt == "user-defined" {
+        let val: Result<Option<String>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(quote_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "user-defined", e))
+        };
+    }
+
+    // Handle Binary Types (hex escape format, e.g. '\x48656c6c6f')
+    if dt == "bytea" {
+        let val: Result<Option<Vec<u8>>, _> = row.try_get(column);
+        return match val {
+            Ok(Some(v)) => Ok(bytea_literal(&v)),
+            Ok(None) => Ok("NULL".to_string()),
+            Err(e) => Err(decode_error(column, "bytea", e))
         };
     }
 
     // Handle String-like types (and fallback)
     let val: Result<Option<String>, _> = row.try_get(column);
     match val {
-        Ok(Some(v)) => Ok(format!("'{}'", v.replace('\'', "''"))),
+        Ok(Some(v)) => Ok(quote_literal(&v)),
         Ok(None) => Ok("NULL".to_string()),
         Err(_) => {
             // Last resort fallback
@@ -511,8 +4762,685 @@ fn get_column_value_as_sql(row: &PgRow, column: &str, data_type: &str) -> Result
             if is_null {
                 Ok("NULL".to_string())
             } else {
-                Err(format!("Unsupported or unreadable data type '{}' for column '{}'", data_type, column))
+                Err(MigrateError::UnsupportedType {
+                    column: column.to_string(),
+                    data_type: data_type.to_string(),
+                })
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "order\"items";
+    const COLUMN: &str = "\"; DROP TABLE users; --";
+
+    fn column(name: &str, data_type: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            udt_schema: "pg_catalog".to_string(),
+            udt_name: data_type.to_string(),
+            is_nullable: true,
+            column_default: None,
+            is_primary_key: false,
+            ordinal_position: 1,
+            identity_generation: None,
+            generation_expression: None,
+            formatted_type: data_type.to_string(),
+            collation: None,
+        }
+    }
+
+    fn positioned(name: &str, ordinal_position: i32) -> ColumnInfo {
+        ColumnInfo {
+            ordinal_position,
+            ..column(name, "integer")
+        }
+    }
+
+    fn names(columns: &[ColumnInfo]) -> Vec<&str> {
+        columns.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn target_columns_follow_target_order() {
+        let source = vec![positioned("a", 1), positioned("b", 2), positioned("c", 3)];
+        let target = vec![positioned("c", 1), positioned("a", 2), positioned("b", 3)];
+        let (kept, skipped) = order_by_target_columns(source, &target, &TableSelection::default());
+        assert_eq!(names(&kept), ["c", "a", "b"]);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn target_columns_skip_missing_and_generated() {
+        let source = vec![
+            positioned("id", 1),
+            positioned("gone", 2),
+            positioned("total", 3),
+        ];
+        let target = vec![
+            positioned("id", 1),
+            ColumnInfo {
+                generation_expression: Some("price * qty".to_string()),
+                ..positioned("total", 2)
+            },
+        ];
+        let (kept, skipped) = order_by_target_columns(source, &target, &TableSelection::default());
+        assert_eq!(names(&kept), ["id"]);
+        assert_eq!(names(&skipped), ["gone", "total"]);
+    }
+
+    #[test]
+    fn target_columns_match_mapped_names() {
+        let source = vec![positioned("id", 1), positioned("email", 2)];
+        let target = vec![positioned("email_address", 1), positioned("id", 2)];
+        let selection = TableSelection {
+            column_map: HashMap::from([("email".to_string(), "email_address".to_string())]),
+            ..TableSelection::default()
+        };
+        let (kept, skipped) = order_by_target_columns(source, &target, &selection);
+        assert_eq!(names(&kept), ["email", "id"]);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn quote_ident_doubles_embedded_quotes() {
+        assert_eq!(quote_ident(TABLE), "\"order\"\"items\"");
+        assert_eq!(quote_ident(COLUMN), "\"\"\"; DROP TABLE users; --\"");
+    }
+
+    #[test]
+    fn keyset_query_quotes_key_column() {
+        let table = format!("{}.{}", quote_ident("public"), quote_ident(TABLE));
+        let select_list = select_column_expr(&column(COLUMN, "ARRAY"));
+        assert_eq!(
+            select_list,
+            "\"\"\"; DROP TABLE users; --\"::text AS \"\"\"; DROP TABLE users; --\""
+        );
+
+        let sql = keyset_query(&select_list, &table, COLUMN, Some("42"), None, 500);
+        assert_eq!(
+            sql,
+            format!(
+                "SELECT {} FROM \"public\".\"order\"\"items\" \
+                 WHERE \"\"\"; DROP TABLE users; --\" > 42 \
+                 ORDER BY \"\"\"; DROP TABLE users; --\" LIMIT 500",
+                select_list
+            )
+        );
+
+        let first = keyset_query("*", &table, COLUMN, None, Some("x = 1"), 10);
+        assert_eq!(
+            first,
+            "SELECT * FROM \"public\".\"order\"\"items\" WHERE (x = 1) \
+             ORDER BY \"\"\"; DROP TABLE users; --\" LIMIT 10"
+        );
+    }
+
+    fn time(hour: u32, min: u32, sec: u32, micro: u32) -> chrono::NaiveTime {
+        chrono::NaiveTime::from_hms_micro_opt(hour, min, sec, micro).unwrap()
+    }
+
+    #[test]
+    fn time_literal_keeps_only_needed_fraction() {
+        assert_eq!(time_literal(time(12, 0, 0, 0)), "12:00:00");
+        assert_eq!(time_literal(time(12, 0, 0, 1)), "12:00:00.000001");
+        assert_eq!(time_literal(time(12, 0, 0, 500_000)), "12:00:00.500000");
+    }
+
+    #[test]
+    fn timestamp_literals_render_date_and_time() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let cases = [
+            (time(12, 0, 0, 0), "2024-02-29 12:00:00"),
+            (time(12, 0, 0, 1), "2024-02-29 12:00:00.000001"),
+            (time(12, 0, 0, 500_000), "2024-02-29 12:00:00.500000"),
+        ];
+        for (time, expected) in cases {
+            let value = date.and_time(time);
+            assert_eq!(timestamp_literal(value), format!("'{}'", expected));
+            assert_eq!(
+                timestamptz_literal(value.and_utc()),
+                format!("'{}+00'", expected)
+            );
+        }
+    }
+
+    #[test]
+    fn oid_is_supported_and_rendered_unsigned() {
+        assert!(is_supported_type("oid"));
+        assert!(is_supported_type("OID"));
+        let large = i32::MAX as u32 + 1;
+        assert_eq!(oid_literal(sqlx::postgres::types::Oid(large)), "2147483648");
+        assert_eq!(
+            oid_literal(sqlx::postgres::types::Oid(u32::MAX)),
+            "4294967295"
+        );
+    }
+
+    #[test]
+    fn insert_statement_quotes_table_and_columns() {
+        let table = format!("{}.{}", quote_ident("public"), quote_ident(TABLE));
+        let columns = [quote_ident("id"), quote_ident(COLUMN)].join(", ");
+        let sql = insert_statement(
+            &table,
+            &columns,
+            "",
+            &["(1, 'a')".to_string(), "(2, 'b')".to_string()],
+            " ON CONFLICT DO NOTHING",
+        );
+        assert_eq!(
+            sql,
+            "INSERT INTO \"public\".\"order\"\"items\" (\"id\", \"\"\"; DROP TABLE users; --\") \
+             VALUES (1, 'a'), (2, 'b') ON CONFLICT DO NOTHING"
+        );
+    }
+
+    #[test]
+    fn insert_chunks_cut_at_max_rows() {
+        let values = vec!["(1)".to_string(); 5];
+        assert_eq!(insert_chunks(&values, 2), [0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn insert_chunks_cut_at_max_bytes() {
+        let half = "x".repeat(MAX_INSERT_BYTES / 2);
+        let values = vec![half.clone(), half.clone(), "(1)".to_string()];
+        assert_eq!(insert_chunks(&values, 100), [0..2, 2..3]);
+    }
+
+    #[test]
+    fn insert_chunks_give_an_oversized_row_its_own_chunk() {
+        let values = vec![
+            "(1)".to_string(),
+            "x".repeat(MAX_INSERT_BYTES + 1),
+            "(3)".to_string(),
+        ];
+        assert_eq!(insert_chunks(&values, 100), [0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn insert_chunks_of_nothing_is_empty() {
+        assert!(insert_chunks(&[], 100).is_empty());
+    }
+
+    #[test]
+    fn serial_defaults_are_not_copied_verbatim() {
+        let serial = ColumnInfo {
+            column_default: Some("nextval('users_id_seq'::regclass)".to_string()),
+            ..column("id", "integer")
+        };
+        assert!(!has_verbatim_default(&serial, false));
+        // With preserve_sequences the sequence is created, so the default is kept as is
+        assert!(has_verbatim_default(&serial, true));
+
+        let identity = ColumnInfo {
+            identity_generation: Some("ALWAYS".to_string()),
+            ..serial.clone()
+        };
+        assert!(!has_verbatim_default(&identity, true));
+    }
+
+    #[test]
+    fn other_defaults_are_copied_verbatim() {
+        let created_at = ColumnInfo {
+            column_default: Some("now()".to_string()),
+            ..column("created_at", "timestamp with time zone")
+        };
+        assert!(has_verbatim_default(&created_at, false));
+
+        // A sequence default on a non-integer column can't become SERIAL
+        let code = ColumnInfo {
+            column_default: Some("('C'::text || nextval('code_seq'::regclass))".to_string()),
+            ..column("code", "text")
+        };
+        assert!(has_verbatim_default(&code, false));
+
+        assert!(!has_verbatim_default(&column("name", "text"), false));
+    }
+
+    fn compiled(transform: ColumnTransform) -> CompiledTransform {
+        transform.compile().unwrap()
+    }
+
+    #[test]
+    fn transforms_pass_null_through() {
+        for transform in [
+            ColumnTransform::Hash,
+            ColumnTransform::Null,
+            ColumnTransform::Constant("redacted".to_string()),
+            ColumnTransform::RegexpReplace {
+                pattern: ".".to_string(),
+                replacement: "x".to_string(),
+            },
+        ] {
+            assert_eq!(compiled(transform).apply("NULL".to_string()), "NULL");
+        }
+        assert_eq!(
+            compiled(ColumnTransform::Null).apply("'alice'".to_string()),
+            "NULL"
+        );
+    }
+
+    #[test]
+    fn hash_is_stable_and_ignores_quoting() {
+        let hash = compiled(ColumnTransform::Hash);
+        let expected = "'ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad'";
+        assert_eq!(hash.apply("'abc'".to_string()), expected);
+        assert_eq!(hash.apply("'abc'".to_string()), expected);
+        // A number is hashed by its text, like the same value stored as text
+        assert_eq!(hash.apply("42".to_string()), hash.apply("'42'".to_string()));
+    }
+
+    #[test]
+    fn constant_and_regex_values_are_quoted() {
+        assert_eq!(
+            compiled(ColumnTransform::Constant("O'Brien".to_string())).apply("'x'".to_string()),
+            "'O''Brien'"
+        );
+        let mask = compiled(ColumnTransform::RegexpReplace {
+            pattern: "@.*$".to_string(),
+            replacement: "@example'test".to_string(),
+        });
+        assert_eq!(
+            mask.apply("'o''brien@mail.com'".to_string()),
+            "'o''brien@example''test'"
+        );
+    }
+
+    #[test]
+    fn transforms_must_name_existing_columns() {
+        let selection = TableSelection {
+            transforms: HashMap::from([("ssn".to_string(), ColumnTransform::Null)]),
+            ..TableSelection::default()
+        };
+        let columns = [column("id", "integer")];
+        let error = compile_transforms(&selection, &columns, &columns)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("Transformed column ssn does not exist"));
+    }
+
+    #[test]
+    fn literal_to_text_unwraps_literals() {
+        assert_eq!(literal_to_text("NULL".to_string()), None);
+        assert_eq!(literal_to_text("42".to_string()).as_deref(), Some("42"));
+        assert_eq!(
+            literal_to_text("'it''s'".to_string()).as_deref(),
+            Some("it's")
+        );
+        assert_eq!(
+            literal_to_text("B'1010'".to_string()).as_deref(),
+            Some("1010")
+        );
+        assert_eq!(
+            literal_to_text("E'a\\nb\\\\c\\'d''e'".to_string()).as_deref(),
+            Some("a\nb\\c'd'e")
+        );
+    }
+
+    fn table_schema(columns: &[&str], primary_key: &[&str]) -> TableSchema {
+        TableSchema {
+            table_name: "users".to_string(),
+            schema_name: "public".to_string(),
+            columns: columns.iter().map(|c| column(c, "text")).collect(),
+            primary_key_columns: primary_key.iter().map(|c| c.to_string()).collect(),
+            check_constraints: Vec::new(),
+            partition_key: None,
+            partitions: Vec::new(),
+            matview_definition: None,
+            create_statement: String::new(),
+        }
+    }
+
+    fn targets(columns: &[&str]) -> Vec<String> {
+        columns.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn conflict_clause_do_nothing() {
+        let schema = table_schema(&["id", "email"], &["id"]);
+        let selection = TableSelection::default();
+        assert_eq!(
+            conflict_clause(ConflictMode::DoNothing, None, &schema, &selection).unwrap(),
+            " ON CONFLICT DO NOTHING"
+        );
+        let target = targets(&["email"]);
+        assert_eq!(
+            conflict_clause(ConflictMode::DoNothing, Some(&target), &schema, &selection).unwrap(),
+            " ON CONFLICT (\"email\") DO NOTHING"
+        );
+        assert_eq!(
+            conflict_clause(ConflictMode::Error, Some(&target), &schema, &selection).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn conflict_clause_upserts_on_primary_key() {
+        let schema = table_schema(&["id", "email", "name"], &["id"]);
+        assert_eq!(
+            conflict_clause(
+                ConflictMode::Upsert,
+                None,
+                &schema,
+                &TableSelection::default()
+            )
+            .unwrap(),
+            " ON CONFLICT (\"id\") DO UPDATE SET \"email\" = EXCLUDED.\"email\", \
+             \"name\" = EXCLUDED.\"name\""
+        );
+    }
+
+    #[test]
+    fn conflict_clause_upserts_on_explicit_target() {
+        let schema = table_schema(&["id", "email", "name"], &["id"]);
+        let target = targets(&["email"]);
+        assert_eq!(
+            conflict_clause(
+                ConflictMode::Upsert,
+                Some(&target),
+                &schema,
+                &TableSelection::default()
+            )
+            .unwrap(),
+            " ON CONFLICT (\"email\") DO UPDATE SET \"id\" = EXCLUDED.\"id\", \
+             \"name\" = EXCLUDED.\"name\""
+        );
+    }
+
+    #[test]
+    fn conflict_clause_uses_mapped_column_names() {
+        let schema = table_schema(&["id", "email"], &["id"]);
+        let selection = TableSelection {
+            column_map: HashMap::from([
+                ("id".to_string(), "user_id".to_string()),
+                ("email".to_string(), "email_address".to_string()),
+            ]),
+            ..TableSelection::default()
+        };
+        assert_eq!(
+            conflict_clause(ConflictMode::Upsert, None, &schema, &selection).unwrap(),
+            " ON CONFLICT (\"user_id\") DO UPDATE SET \
+             \"email_address\" = EXCLUDED.\"email_address\""
+        );
+    }
+
+    #[test]
+    fn conflict_clause_upsert_needs_a_primary_key() {
+        let schema = table_schema(&["id", "email"], &[]);
+        let error = conflict_clause(
+            ConflictMode::Upsert,
+            None,
+            &schema,
+            &TableSelection::default(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("requires a primary key"));
+    }
+
+    #[test]
+    fn bytea_literal_is_lowercase_hex_escape() {
+        assert_eq!(bytea_literal(b"Hello"), "'\\x48656c6c6f'");
+        assert_eq!(bytea_literal(&[0x00, 0x27, 0xff]), "'\\x0027ff'");
+        assert_eq!(bytea_literal(&[]), "'\\x'");
+    }
+
+    #[test]
+    fn text_like_literals_double_quotes() {
+        assert_eq!(
+            quote_literal("<a title='it''s'>x</a>"),
+            "'<a title=''it''''s''>x</a>'"
+        );
+        assert_eq!(
+            quote_literal("'o''neil':1 'cat':2"),
+            "'''o''''neil'':1 ''cat'':2'"
+        );
+        assert_eq!(quote_literal(""), "''");
+    }
+
+    #[test]
+    fn bit_literal_prefixes_the_quoted_bits() {
+        assert_eq!(bit_literal("1010"), "B'1010'");
+        assert_eq!(bit_literal(""), "B''");
+    }
+
+    #[test]
+    fn money_literal_is_quoted_numeric() {
+        let value: bigdecimal::BigDecimal = "-1234.50".parse().unwrap();
+        assert_eq!(money_literal(&value), "'-1234.50'");
+    }
+
+    #[test]
+    fn interval_literal_keeps_months_days_and_microseconds_apart() {
+        let value = sqlx::postgres::types::PgInterval {
+            months: 14,
+            days: -3,
+            microseconds: 1_500_000,
+        };
+        assert_eq!(
+            interval_literal(&value),
+            "'14 months -3 days 1500000 microseconds'"
+        );
+    }
+
+    #[test]
+    fn timetz_literal_keeps_the_offset() {
+        let value = sqlx::postgres::types::PgTimeTz {
+            time: time(9, 30, 0, 250),
+            offset: chrono::FixedOffset::east_opt(5 * 3600 + 1800).unwrap(),
+        };
+        assert_eq!(timetz_literal(&value), "'09:30:00.000250+05:30'");
+
+        let value = sqlx::postgres::types::PgTimeTz {
+            time: time(23, 0, 0, 0),
+            offset: chrono::FixedOffset::west_opt(8 * 3600).unwrap(),
+        };
+        assert_eq!(timetz_literal(&value), "'23:00:00-08:00'");
+    }
+
+    #[test]
+    fn missing_target_columns_adds_nullable_columns() {
+        let target = table_schema(&["id", "email"], &["id"]);
+        let source = vec![
+            column("id", "text"),
+            column("email", "text"),
+            column("nickname", "text"),
+        ];
+        let missing = missing_target_columns(&source, &target).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].name, "nickname");
+    }
+
+    #[test]
+    fn missing_target_columns_adds_not_null_columns_with_a_default() {
+        let target = table_schema(&["id"], &["id"]);
+        let source = vec![
+            column("id", "text"),
+            ColumnInfo {
+                is_nullable: false,
+                column_default: Some("'active'::text".to_string()),
+                ..column("status", "text")
+            },
+            ColumnInfo {
+                is_nullable: false,
+                identity_generation: Some("ALWAYS".to_string()),
+                ..column("seq", "bigint")
+            },
+        ];
+        let missing = missing_target_columns(&source, &target).unwrap();
+        assert_eq!(
+            missing.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            ["status", "seq"]
+        );
+    }
+
+    #[test]
+    fn missing_target_columns_rejects_not_null_without_default() {
+        let target = table_schema(&["id"], &["id"]);
+        let source = vec![
+            column("id", "text"),
+            ColumnInfo {
+                is_nullable: false,
+                ..column("email", "text")
+            },
+        ];
+        let err = missing_target_columns(&source, &target)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("public.users"), "{err}");
+        assert!(
+            err.contains("column email is missing in the target and can't be added"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_target_columns_reports_type_mismatches() {
+        let target = table_schema(&["id", "age"], &["id"]);
+        let source = vec![
+            column("id", "text"),
+            column("age", "integer"),
+            ColumnInfo {
+                is_nullable: false,
+                ..column("email", "text")
+            },
+        ];
+        let err = missing_target_columns(&source, &target)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("column age is integer in the source but text in the target"),
+            "{err}"
+        );
+        assert!(err.contains("; column email is missing"), "{err}");
+    }
+
+    #[test]
+    fn missing_target_columns_empty_when_tables_match() {
+        let target = table_schema(&["id", "email"], &["id"]);
+        let source = vec![column("id", "text"), column("email", "text")];
+        assert!(missing_target_columns(&source, &target).unwrap().is_empty());
+    }
+
+    #[test]
+    fn progress_throttle_steps_by_whole_percent() {
+        let mut throttle = ProgressThrottle::default();
+        assert!(throttle.due(0, 1000));
+        assert!(!throttle.due(5, 1000));
+        assert!(throttle.due(10, 1000));
+        assert!(!throttle.due(19, 1000));
+        assert!(throttle.due(20, 1000));
+    }
+
+    #[test]
+    fn progress_throttle_without_total_waits_for_the_interval() {
+        let mut throttle = ProgressThrottle::default();
+        assert!(throttle.due(0, 0));
+        assert!(!throttle.due(500, 0));
+        throttle.last_emit = Some(
+            std::time::Instant::now() - std::time::Duration::from_millis(PROGRESS_INTERVAL_MS),
+        );
+        assert!(throttle.due(1000, 0));
+        assert!(!throttle.due(1500, 0));
+    }
+
+    #[test]
+    fn progress_throttle_always_emits_completion() {
+        let mut throttle = ProgressThrottle::default();
+        assert!(throttle.due(1000, 1000));
+        assert!(throttle.due(1000, 1000));
+        // The estimate was low; every batch past it is still reported
+        assert!(throttle.due(1001, 1000));
+    }
+
+    #[test]
+    fn throughput_with_zero_elapsed_has_no_rate() {
+        let zero = std::time::Duration::ZERO;
+        assert_eq!(throughput(10, 100, zero), (0.0, None));
+        assert_eq!(throughput(100, 100, zero), (0.0, Some(0)));
+        assert_eq!(throughput(0, 0, zero), (0.0, None));
+    }
+
+    #[test]
+    fn throughput_estimates_remaining_seconds() {
+        let elapsed = std::time::Duration::from_secs(2);
+        assert_eq!(throughput(100, 301, elapsed), (50.0, Some(5)));
+        assert_eq!(throughput(100, 0, elapsed), (50.0, None));
+        assert_eq!(throughput(400, 300, elapsed), (200.0, Some(0)));
+    }
+
+    #[test]
+    fn renamed_for_target_keeps_names_of_an_unrenamed_table() {
+        assert_eq!(
+            renamed_for_target("users_email_idx", "users", "users"),
+            "users_email_idx"
+        );
+        assert_eq!(renamed_for_target("by_email", "users", "users"), "by_email");
+    }
+
+    #[test]
+    fn renamed_for_target_replaces_the_table_prefix() {
+        assert_eq!(
+            renamed_for_target("users_email_idx", "users", "users_backup"),
+            "users_backup_email_idx"
+        );
+        assert_eq!(
+            renamed_for_target("users_pkey", "users", "people"),
+            "people_pkey"
+        );
+        assert_eq!(renamed_for_target("users", "users", "people"), "people");
+    }
+
+    #[test]
+    fn renamed_for_target_prefixes_other_names() {
+        assert_eq!(
+            renamed_for_target("idx_users_email", "users", "people"),
+            "people_idx_users_email"
+        );
+        assert_eq!(
+            renamed_for_target("measurements_2024", "events", "events_copy"),
+            "events_copy_measurements_2024"
+        );
+    }
+
+    #[test]
+    fn renamed_for_target_only_replaces_a_whole_word_prefix() {
+        assert_eq!(
+            renamed_for_target("users_email_idx", "user", "user_backup"),
+            "user_backup_users_email_idx"
+        );
+    }
+
+    #[test]
+    fn transient_codes_are_retried() {
+        for code in [
+            "08000", "08003", "08006", "57P01", "57P02", "57P03", "53300", "40001", "40P01",
+        ] {
+            assert!(is_transient_code(code), "{code}");
+        }
+    }
+
+    #[test]
+    fn other_codes_are_not_retried() {
+        // unique_violation, undefined_table, syntax_error, query_canceled, disk_full
+        for code in ["23505", "42P01", "42601", "57014", "53100"] {
+            assert!(!is_transient_code(code), "{code}");
+        }
+    }
+
+    #[test]
+    fn transient_errors_include_lost_connections() {
+        assert!(is_transient_error(&sqlx::Error::PoolTimedOut));
+        assert!(is_transient_error(&sqlx::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset
+        ))));
+        assert!(!is_transient_error(&sqlx::Error::RowNotFound));
+    }
+}
@@ -1,7 +1,15 @@
+pub mod audit;
+pub mod checkpoint;
 pub mod connection;
+pub mod error;
+pub mod export;
+pub mod libpq;
 pub mod migrate;
 pub mod schema;
+pub mod tunnel;
 
 pub use connection::*;
+pub use error::*;
+pub use export::*;
 pub use migrate::*;
 pub use schema::*;
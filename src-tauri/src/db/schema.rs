@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
-use sqlx::Row;
+use sqlx::{Column, Executor, PgPool, Row};
+use std::collections::{HashMap, HashSet};
+
+use super::error::MigrateError;
 
 /// Table information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,7 +10,45 @@ pub struct TableInfo {
     pub name: String,
     pub schema: String,
     pub row_count: i64,
+    pub row_count_estimated: bool, // row_count comes from planner statistics
     pub size_bytes: i64,
+    /// What the relation is in `pg_class`, telling foreign tables and partitioned parents apart
+    pub relation_kind: RelationKind,
+}
+
+/// Kind of relation, from `pg_class.relkind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    /// Ordinary table (`r`)
+    Table,
+    /// Partitioned table (`p`), its rows live in the partitions
+    PartitionedTable,
+    /// Foreign table (`f`), e.g. from postgres_fdw, its rows live on another server
+    ForeignTable,
+    /// Materialized view (`m`)
+    MaterializedView,
+}
+
+impl RelationKind {
+    /// Map a `pg_class.relkind` code, treating anything unknown as an ordinary table
+    pub fn from_relkind(relkind: &str) -> Self {
+        match relkind {
+            "p" => RelationKind::PartitionedTable,
+            "f" => RelationKind::ForeignTable,
+            "m" => RelationKind::MaterializedView,
+            _ => RelationKind::Table,
+        }
+    }
+}
+
+/// Aggregate size of a database's tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSummary {
+    pub table_count: i64,
+    pub total_rows: i64, // estimated from pg_class.reltuples
+    pub total_size_bytes: i64,
+    pub total_size_pretty: String, // e.g. "1234 MB"
 }
 
 /// Column information
@@ -16,10 +56,16 @@ pub struct TableInfo {
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
+    pub udt_schema: String,
+    pub udt_name: String, // underlying type name; array element types are prefixed with '_'
     pub is_nullable: bool,
     pub column_default: Option<String>,
     pub is_primary_key: bool,
     pub ordinal_position: i32,
+    pub identity_generation: Option<String>, // "ALWAYS" or "BY DEFAULT" for identity columns
+    pub generation_expression: Option<String>, // set for GENERATED ALWAYS AS (...) STORED columns
+    pub formatted_type: String, // declared type with modifiers, e.g. "character varying(50)"
+    pub collation: Option<String>, // quoted, only when it differs from the type's default
 }
 
 /// Foreign key dependency information
@@ -42,6 +88,83 @@ pub struct TableDependency {
     pub depends_on: Vec<(String, String)>, // (schema, table)
 }
 
+/// User-defined enum type used by a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnumType {
+    pub schema: String,
+    pub name: String,
+    pub labels: Vec<String>,
+}
+
+/// User-defined composite type (`CREATE TYPE ... AS (...)`) used by a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeType {
+    pub schema: String,
+    pub name: String,
+    pub fields: Vec<CompositeField>,
+}
+
+/// Field of a composite type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeField {
+    pub name: String,
+    pub data_type: String, // format_type output, e.g. "numeric(10,2)"
+}
+
+/// Extension providing a type used by a table (e.g. hstore)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionInfo {
+    pub name: String,
+    pub schema: String,
+}
+
+/// CHECK constraint on a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub definition: String, // e.g. "CHECK ((price >= 0))"
+}
+
+/// Secondary (non primary key) index on a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub is_unique: bool,
+    pub definition: String, // as returned by pg_get_indexdef
+}
+
+/// Partition of a partitioned table (at any depth)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionInfo {
+    pub schema: String,
+    pub name: String,
+    pub parent_schema: String,
+    pub parent_name: String,
+    pub bound: String, // e.g. "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')"
+    pub partition_key: Option<String>, // set when the partition is itself partitioned
+}
+
+/// Sequence definition and current value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceInfo {
+    pub name: String,
+    pub data_type: String,
+    pub start_value: i64,
+    pub min_value: i64,
+    pub max_value: i64,
+    pub increment_by: i64,
+    pub cycle: bool,
+    pub cache_size: i64,
+    pub last_value: Option<i64>, // None until nextval has been called
+}
+
+/// Table and column comments (COMMENT ON)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableComments {
+    pub table: Option<String>,
+    pub columns: Vec<(String, String)>, // (column, comment)
+}
+
 /// Full table schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableSchema {
@@ -49,28 +172,49 @@ pub struct TableSchema {
     pub schema_name: String,
     pub columns: Vec<ColumnInfo>,
     pub primary_key_columns: Vec<String>,
+    pub check_constraints: Vec<CheckConstraint>,
+    pub partition_key: Option<String>, // e.g. "RANGE (created_at)" for partitioned tables
+    pub partitions: Vec<PartitionInfo>, // parents come before their children
+    /// Defining query, set when the source is a materialized view
+    #[serde(default)]
+    pub matview_definition: Option<String>,
     pub create_statement: String,
 }
 
-/// List all tables in the database
-pub async fn list_tables(pool: &PgPool) -> Result<Vec<TableInfo>, String> {
+/// List all tables in the database. With `estimated`, row counts come from the planner
+/// statistics (`pg_class.reltuples`) instead of an exact COUNT(*) per table.
+pub async fn list_tables(pool: &PgPool, estimated: bool) -> Result<Vec<TableInfo>, MigrateError> {
     let query = r#"
         SELECT 
-            t.table_name,
-            t.table_schema,
-            COALESCE(pg_total_relation_size(c.oid), 0) as size_bytes
+            t.table_name::text AS table_name,
+            t.table_schema::text AS table_schema,
+            COALESCE(c.relkind::text, 'r') AS relkind,
+            COALESCE(pg_total_relation_size(c.oid), 0) as size_bytes,
+            COALESCE(GREATEST(c.reltuples, 0), 0)::bigint as estimated_rows
         FROM information_schema.tables t
         LEFT JOIN pg_catalog.pg_namespace n ON n.nspname = t.table_schema
         LEFT JOIN pg_catalog.pg_class c ON c.relname = t.table_name AND c.relnamespace = n.oid
         WHERE t.table_schema NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
-            AND t.table_type = 'BASE TABLE'
-        ORDER BY t.table_schema, t.table_name
+            AND t.table_type IN ('BASE TABLE', 'FOREIGN')
+        UNION ALL
+        -- Materialized views aren't in information_schema.tables
+        SELECT
+            c.relname::text,
+            n.nspname::text,
+            'm',
+            pg_total_relation_size(c.oid),
+            GREATEST(c.reltuples, 0)::bigint
+        FROM pg_catalog.pg_class c
+        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind = 'm'
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
+        ORDER BY table_schema, table_name
     "#;
 
     let rows = sqlx::query(query)
         .fetch_all(pool)
         .await
-        .map_err(|e| format!("Failed to list tables: {}", e))?;
+        .map_err(|e| MigrateError::sql("Failed to list tables", e))?;
 
     let mut tables = Vec::new();
 
@@ -78,89 +222,374 @@ pub async fn list_tables(pool: &PgPool) -> Result<Vec<TableInfo>, String> {
         let name: String = row.get("table_name");
         let schema: String = row.get("table_schema");
         let size_bytes: i64 = row.get("size_bytes");
+        let relation_kind = RelationKind::from_relkind(row.get("relkind"));
+        // Counting a foreign table scans the remote server, so it only gets the estimate
+        let estimated = estimated || relation_kind == RelationKind::ForeignTable;
 
-        // Fetch EXACT row count for each table
-        let count_query = format!("SELECT COUNT(*) FROM \"{}\".\"{}\"", schema, name);
-        let row_count: i64 = sqlx::query_scalar(&count_query)
-            .fetch_one(pool)
-            .await
-            .unwrap_or(0);
+        let row_count: i64 = if estimated {
+            row.get("estimated_rows")
+        } else {
+            // Fetch EXACT row count for each table
+            let count_query = format!(
+                "SELECT COUNT(*) FROM {}.{}",
+                quote_ident(&schema),
+                quote_ident(&name)
+            );
+            sqlx::query_scalar(&count_query)
+                .fetch_one(pool)
+                .await
+                .unwrap_or(0)
+        };
 
         tables.push(TableInfo {
             name,
             schema,
             row_count,
+            row_count_estimated: estimated,
             size_bytes,
+            relation_kind,
         });
     }
 
     Ok(tables)
 }
 
-/// Get exact row count for a table
-pub async fn get_row_count(pool: &PgPool, schema: &str, table: &str) -> Result<i64, String> {
-    let query = format!(
+/// Kind of a relation, `None` when it doesn't exist
+pub async fn get_relation_kind(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Option<RelationKind>, MigrateError> {
+    let relkind: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT c.relkind::text
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to get relation kind", e))?;
+    Ok(relkind.as_deref().map(RelationKind::from_relkind))
+}
+
+/// `list_tables` in migration order: tables referenced by foreign keys come before the tables
+/// referencing them, otherwise alphabetical by schema and name
+pub async fn list_tables_sorted(
+    pool: &PgPool,
+    estimated: bool,
+) -> Result<Vec<TableInfo>, MigrateError> {
+    let tables = list_tables(pool, estimated).await?;
+    let keys: Vec<(String, String)> = tables
+        .iter()
+        .map(|t| (t.schema.clone(), t.name.clone()))
+        .collect();
+    let order = dependency_order(&keys, &get_all_dependencies(pool).await?);
+
+    let mut tables: Vec<Option<TableInfo>> = tables.into_iter().map(Some).collect();
+    Ok(order.into_iter().filter_map(|i| tables[i].take()).collect())
+}
+
+/// Get table count, estimated total rows and total size of all user tables
+pub async fn get_database_summary(pool: &PgPool) -> Result<DatabaseSummary, MigrateError> {
+    let query = r#"
+        SELECT
+            COUNT(*) AS table_count,
+            COALESCE(SUM(GREATEST(c.reltuples, 0)), 0)::bigint AS total_rows,
+            COALESCE(SUM(pg_total_relation_size(c.oid)), 0)::bigint AS total_size_bytes,
+            pg_size_pretty(COALESCE(SUM(pg_total_relation_size(c.oid)), 0)) AS total_size_pretty
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'p')
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')
+            AND n.nspname NOT LIKE 'pg_temp%'
+    "#;
+
+    let row = sqlx::query(query)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get database summary", e))?;
+
+    Ok(DatabaseSummary {
+        table_count: row.get("table_count"),
+        total_rows: row.get("total_rows"),
+        total_size_bytes: row.get("total_size_bytes"),
+        total_size_pretty: row.get("total_size_pretty"),
+    })
+}
+
+/// Get exact row count for a table, optionally restricted by a row filter
+pub async fn get_row_count(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    filter: Option<&str>,
+) -> Result<i64, MigrateError> {
+    let mut query = format!(
         "SELECT COUNT(*) as count FROM {}.{}",
         quote_ident(schema),
         quote_ident(table)
     );
+    if let Some(filter) = filter {
+        query.push_str(&format!(" WHERE ({})", filter));
+    }
 
     let row = sqlx::query(&query)
         .fetch_one(pool)
         .await
-        .map_err(|e| format!("Failed to count rows: {}", e))?;
+        .map_err(|e| MigrateError::sql("Failed to count rows", e))?;
 
     Ok(row.get::<i64, _>("count"))
 }
 
+/// Whether the table has any (matching) rows; stops at the first one instead of counting
+pub async fn table_has_rows(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    filter: Option<&str>,
+) -> Result<bool, MigrateError> {
+    let mut query = format!(
+        "SELECT EXISTS(SELECT 1 FROM {}.{}",
+        quote_ident(schema),
+        quote_ident(table)
+    );
+    if let Some(filter) = filter {
+        query.push_str(&format!(" WHERE ({})", filter));
+    }
+    query.push_str(" LIMIT 1)");
+
+    sqlx::query_scalar(&query)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to check for rows", e))
+}
+
+/// Estimated row count (`pg_class.reltuples`) and total size including indexes and TOAST
+pub async fn get_table_size_estimate(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<(i64, i64), MigrateError> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            GREATEST(c.reltuples, 0)::bigint AS estimated_rows,
+            pg_total_relation_size(c.oid) AS size_bytes
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to estimate table size", e))?
+    .ok_or_else(|| MigrateError::Other(format!("Table {}.{} not found", schema, table)))?;
+
+    Ok((row.get("estimated_rows"), row.get("size_bytes")))
+}
+
+/// Check that a row filter is valid SQL for a table without reading any rows
+pub async fn validate_filter(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    filter: &str,
+) -> Result<(), MigrateError> {
+    let query = format!(
+        "SELECT 1 FROM {}.{} WHERE ({}) LIMIT 0",
+        quote_ident(schema),
+        quote_ident(table),
+        filter
+    );
+
+    sqlx::query(&query)
+        .execute(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Invalid filter", e))?;
+
+    Ok(())
+}
+
+/// Column query shared by `get_table_schema` and `get_table_schemas`, without its WHERE clause.
+/// Reads from the catalog rather than information_schema.columns, which leaves out
+/// materialized views; data_type/udt_* are derived the same way information_schema does.
+const COLUMNS_SELECT: &str = r#"
+        SELECT
+            n.nspname::text AS table_schema,
+            c.relname::text AS table_name,
+            a.attname::text AS column_name,
+            CASE
+                WHEN bt.typelem <> 0 AND bt.typlen = -1 THEN 'ARRAY'
+                WHEN bn.nspname = 'pg_catalog' THEN format_type(bt.oid, NULL)
+                ELSE 'USER-DEFINED'
+            END AS data_type,
+            bn.nspname::text AS udt_schema,
+            bt.typname::text AS udt_name,
+            NOT a.attnotnull AS is_nullable,
+            CASE WHEN a.attgenerated = '' THEN pg_get_expr(ad.adbin, ad.adrelid) END AS column_default,
+            a.attnum::int AS ordinal_position,
+            CASE a.attidentity WHEN 'a' THEN 'ALWAYS' WHEN 'd' THEN 'BY DEFAULT' END AS identity_generation,
+            CASE WHEN a.attgenerated = 's' THEN pg_get_expr(ad.adbin, ad.adrelid) END AS generation_expression,
+            COALESCE(a.attnum = ANY(pk.conkey), false) AS is_primary_key,
+            format_type(
+                bt.oid,
+                CASE WHEN t.typbasetype <> 0 THEN t.typtypmod ELSE a.atttypmod END
+            ) AS formatted_type,
+            CASE WHEN a.attcollation <> t.typcollation THEN
+                quote_ident(colln.nspname) || '.' || quote_ident(coll.collname)
+            END AS collation
+        FROM pg_catalog.pg_attribute a
+        JOIN pg_catalog.pg_class c ON c.oid = a.attrelid
+        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_catalog.pg_type t ON t.oid = a.atttypid
+        -- Domains are resolved to their base type, since they aren't recreated on the target
+        JOIN pg_catalog.pg_type bt ON bt.oid = COALESCE(NULLIF(t.typbasetype, 0), t.oid)
+        JOIN pg_catalog.pg_namespace bn ON bn.oid = bt.typnamespace
+        LEFT JOIN pg_catalog.pg_attrdef ad ON ad.adrelid = a.attrelid AND ad.adnum = a.attnum
+        LEFT JOIN pg_catalog.pg_constraint pk ON pk.conrelid = c.oid AND pk.contype = 'p'
+        LEFT JOIN pg_catalog.pg_collation coll ON coll.oid = a.attcollation
+        LEFT JOIN pg_catalog.pg_namespace colln ON colln.oid = coll.collnamespace
+"#;
+
+fn column_from_row(row: &sqlx::postgres::PgRow) -> ColumnInfo {
+    ColumnInfo {
+        name: row.get("column_name"),
+        data_type: row.get("data_type"),
+        udt_schema: row.get("udt_schema"),
+        udt_name: row.get("udt_name"),
+        is_nullable: row.get("is_nullable"),
+        column_default: row.get("column_default"),
+        ordinal_position: row.get("ordinal_position"),
+        is_primary_key: row.get("is_primary_key"),
+        identity_generation: row.get("identity_generation"),
+        generation_expression: row.get("generation_expression"),
+        formatted_type: row.get("formatted_type"),
+        collation: row.get("collation"),
+    }
+}
+
+/// Schema of the result of a SELECT, as a table `schema.table` with those columns. Column
+/// types come from the statement description, resolved the way `COLUMNS_SELECT` does.
+pub async fn get_query_schema(
+    pool: &PgPool,
+    sql: &str,
+    schema: &str,
+    table: &str,
+) -> Result<TableSchema, MigrateError> {
+    let described = pool
+        .describe(sql)
+        .await
+        .map_err(|e| MigrateError::sql("Invalid source query", e))?;
+    let oids: Vec<i64> = described
+        .columns()
+        .iter()
+        .filter_map(|c| c.type_info().oid())
+        .map(|oid| oid.0 as i64)
+        .collect();
+
+    let types = sqlx::query(
+        r#"
+        SELECT
+            t.oid::int8 AS oid,
+            CASE
+                WHEN bt.typelem <> 0 AND bt.typlen = -1 THEN 'ARRAY'
+                WHEN bn.nspname = 'pg_catalog' THEN format_type(bt.oid, NULL)
+                ELSE 'USER-DEFINED'
+            END AS data_type,
+            bn.nspname::text AS udt_schema,
+            bt.typname::text AS udt_name,
+            format_type(bt.oid, NULLIF(t.typtypmod, -1)) AS formatted_type
+        FROM pg_catalog.pg_type t
+        JOIN pg_catalog.pg_type bt ON bt.oid = COALESCE(NULLIF(t.typbasetype, 0), t.oid)
+        JOIN pg_catalog.pg_namespace bn ON bn.oid = bt.typnamespace
+        WHERE t.oid::int8 = ANY($1)
+        "#,
+    )
+    .bind(&oids)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to read source query types", e))?;
+
+    let mut columns: Vec<ColumnInfo> = Vec::new();
+    for (i, column) in described.columns().iter().enumerate() {
+        if columns.iter().any(|c| c.name == column.name()) {
+            return Err(MigrateError::Other(format!(
+                "Source query returns column {} more than once, give the columns distinct aliases",
+                column.name()
+            )));
+        }
+        let oid = column.type_info().oid().map(|oid| oid.0 as i64);
+        let Some(row) = types.iter().find(|r| Some(r.get::<i64, _>("oid")) == oid) else {
+            return Err(MigrateError::Other(format!(
+                "Type of source query column {} could not be determined",
+                column.name()
+            )));
+        };
+        columns.push(ColumnInfo {
+            name: column.name().to_string(),
+            data_type: row.get("data_type"),
+            udt_schema: row.get("udt_schema"),
+            udt_name: row.get("udt_name"),
+            // Expressions are reported as unknown
+            is_nullable: described.nullable(i).unwrap_or(true),
+            column_default: None,
+            is_primary_key: false,
+            ordinal_position: i as i32 + 1,
+            identity_generation: None,
+            generation_expression: None,
+            formatted_type: row.get("formatted_type"),
+            collation: None,
+        });
+    }
+
+    let create_statement =
+        generate_create_table_statement(schema, table, &columns, &[], &[], None, false);
+    Ok(TableSchema {
+        table_name: table.to_string(),
+        schema_name: schema.to_string(),
+        columns,
+        primary_key_columns: Vec::new(),
+        check_constraints: Vec::new(),
+        partition_key: None,
+        partitions: Vec::new(),
+        matview_definition: None,
+        create_statement,
+    })
+}
+
 /// Get table schema (columns, types, constraints)
 pub async fn get_table_schema(
     pool: &PgPool,
     schema: &str,
     table: &str,
-) -> Result<TableSchema, String> {
+) -> Result<TableSchema, MigrateError> {
     // Get columns
-    let columns_query = r#"
-        SELECT 
-            c.column_name,
-            c.data_type,
-            c.is_nullable = 'YES' as is_nullable,
-            c.column_default,
-            c.ordinal_position,
-            CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key
-        FROM information_schema.columns c
-        LEFT JOIN (
-            SELECT kcu.column_name
-            FROM information_schema.table_constraints tc
-            JOIN information_schema.key_column_usage kcu 
-                ON tc.constraint_name = kcu.constraint_name
-                AND tc.table_schema = kcu.table_schema
-            WHERE tc.table_schema = $1 
-                AND tc.table_name = $2 
-                AND tc.constraint_type = 'PRIMARY KEY'
-        ) pk ON c.column_name = pk.column_name
-        WHERE c.table_schema = $1 AND c.table_name = $2
-        ORDER BY c.ordinal_position
-    "#;
+    let columns_query = format!(
+        r#"{}
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            -- System columns (ctid, xmin, the oid of WITH OIDS tables, ...) have attnum < 0
+            AND a.attnum > 0
+            AND NOT a.attisdropped
+        ORDER BY a.attnum
+    "#,
+        COLUMNS_SELECT
+    );
 
-    let rows = sqlx::query(columns_query)
+    let rows = sqlx::query(&columns_query)
         .bind(schema)
         .bind(table)
         .fetch_all(pool)
         .await
-        .map_err(|e| format!("Failed to get columns: {}", e))?;
+        .map_err(|e| MigrateError::sql("Failed to get columns", e))?;
 
-    let columns: Vec<ColumnInfo> = rows
-        .iter()
-        .map(|row| ColumnInfo {
-            name: row.get("column_name"),
-            data_type: row.get("data_type"),
-            is_nullable: row.get("is_nullable"),
-            column_default: row.get("column_default"),
-            ordinal_position: row.get("ordinal_position"),
-            is_primary_key: row.get("is_primary_key"),
-        })
-        .collect();
+    let columns: Vec<ColumnInfo> = rows.iter().map(column_from_row).collect();
 
     let primary_key_columns: Vec<String> = columns
         .iter()
@@ -168,25 +597,231 @@ pub async fn get_table_schema(
         .map(|c| c.name.clone())
         .collect();
 
+    // Get CHECK constraints
+    let checks_query = r#"
+        SELECT
+            con.conname::text AS constraint_name,
+            pg_get_constraintdef(con.oid) AS definition
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND con.contype = 'c'
+        ORDER BY con.conname
+    "#;
+
+    let check_constraints: Vec<CheckConstraint> = sqlx::query(checks_query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get check constraints", e))?
+        .iter()
+        .map(|row| CheckConstraint {
+            name: row.get("constraint_name"),
+            definition: row.get("definition"),
+        })
+        .collect();
+
+    // Get the partition key (only set for partitioned tables)
+    let partition_key: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT pg_get_partkeydef(c.oid)
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND c.relkind = 'p'
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to get partition key", e))?;
+
+    let partitions = if partition_key.is_some() {
+        get_table_partitions(pool, schema, table).await?
+    } else {
+        Vec::new()
+    };
+
+    let matview_definition: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT pg_get_viewdef(c.oid)
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND c.relkind = 'm'
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to get materialized view definition", e))?;
+
     // Generate CREATE TABLE statement
-    let create_statement =
-        generate_create_table_statement(schema, table, &columns, &primary_key_columns);
+    let create_statement = generate_create_table_statement(
+        schema,
+        table,
+        &columns,
+        &primary_key_columns,
+        &check_constraints,
+        partition_key.as_deref(),
+        false,
+    );
 
     Ok(TableSchema {
         table_name: table.to_string(),
         schema_name: schema.to_string(),
         columns,
         primary_key_columns,
+        check_constraints,
+        partition_key,
+        partitions,
+        matview_definition,
         create_statement,
     })
 }
 
+/// Schemas of several tables at once: columns, primary keys, CHECK constraints, partition keys
+/// and materialized view definitions are each read for all tables in a single query, so
+/// comparing many tables doesn't cost a round trip per table. Tables that don't exist are left
+/// out; the rest are returned in the requested order.
+pub async fn get_table_schemas(
+    pool: &PgPool,
+    tables: Vec<(String, String)>,
+) -> Result<Vec<TableSchema>, MigrateError> {
+    if tables.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (schemas, names): (Vec<String>, Vec<String>) = tables.iter().cloned().unzip();
+    let mut found: HashMap<(String, String), TableSchema> = HashMap::new();
+
+    let columns_query = format!(
+        r#"{}
+        WHERE (n.nspname, c.relname) IN (SELECT * FROM unnest($1::text[], $2::text[]))
+            AND a.attnum > 0
+            AND NOT a.attisdropped
+        ORDER BY n.nspname, c.relname, a.attnum
+    "#,
+        COLUMNS_SELECT
+    );
+    let rows = sqlx::query(&columns_query)
+        .bind(&schemas)
+        .bind(&names)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get columns", e))?;
+    for row in &rows {
+        let key: (String, String) = (row.get("table_schema"), row.get("table_name"));
+        let column = column_from_row(row);
+        let schema = found.entry(key.clone()).or_insert_with(|| TableSchema {
+            table_name: key.1,
+            schema_name: key.0,
+            columns: Vec::new(),
+            primary_key_columns: Vec::new(),
+            check_constraints: Vec::new(),
+            partition_key: None,
+            partitions: Vec::new(),
+            matview_definition: None,
+            create_statement: String::new(),
+        });
+        if column.is_primary_key {
+            schema.primary_key_columns.push(column.name.clone());
+        }
+        schema.columns.push(column);
+    }
+
+    let checks = sqlx::query(
+        r#"
+        SELECT
+            n.nspname::text AS table_schema,
+            c.relname::text AS table_name,
+            con.conname::text AS constraint_name,
+            pg_get_constraintdef(con.oid) AS definition
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE (n.nspname, c.relname) IN (SELECT * FROM unnest($1::text[], $2::text[]))
+            AND con.contype = 'c'
+        ORDER BY con.conname
+        "#,
+    )
+    .bind(&schemas)
+    .bind(&names)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to get check constraints", e))?;
+    for row in &checks {
+        if let Some(schema) = found.get_mut(&(row.get("table_schema"), row.get("table_name"))) {
+            schema.check_constraints.push(CheckConstraint {
+                name: row.get("constraint_name"),
+                definition: row.get("definition"),
+            });
+        }
+    }
+
+    let relations = sqlx::query(
+        r#"
+        SELECT
+            n.nspname::text AS table_schema,
+            c.relname::text AS table_name,
+            CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END AS partition_key,
+            CASE WHEN c.relkind = 'm' THEN pg_get_viewdef(c.oid) END AS matview_definition
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE (n.nspname, c.relname) IN (SELECT * FROM unnest($1::text[], $2::text[]))
+            AND c.relkind IN ('p', 'm')
+        "#,
+    )
+    .bind(&schemas)
+    .bind(&names)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| MigrateError::sql("Failed to get partition keys", e))?;
+    for row in &relations {
+        if let Some(schema) = found.get_mut(&(row.get("table_schema"), row.get("table_name"))) {
+            schema.partition_key = row.get("partition_key");
+            schema.matview_definition = row.get("matview_definition");
+        }
+    }
+
+    let mut result = Vec::new();
+    for key in tables {
+        let Some(mut schema) = found.remove(&key) else {
+            continue;
+        };
+        // Partition trees are rare enough to read one table at a time
+        if schema.partition_key.is_some() {
+            schema.partitions = get_table_partitions(pool, &key.0, &key.1).await?;
+        }
+        schema.create_statement = generate_create_table_statement(
+            &key.0,
+            &key.1,
+            &schema.columns,
+            &schema.primary_key_columns,
+            &schema.check_constraints,
+            schema.partition_key.as_deref(),
+            false,
+        );
+        result.push(schema);
+    }
+    Ok(result)
+}
+
 /// Generate CREATE TABLE statement from schema info
-fn generate_create_table_statement(
+pub fn generate_create_table_statement(
     schema: &str,
     table: &str,
     columns: &[ColumnInfo],
     primary_keys: &[String],
+    check_constraints: &[CheckConstraint],
+    partition_key: Option<&str>,
+    preserve_sequences: bool,
 ) -> String {
     let mut sql = format!(
         "CREATE TABLE {}.{} (\n",
@@ -197,40 +832,10 @@ fn generate_create_table_statement(
     let column_defs: Vec<String> = columns
         .iter()
         .map(|col| {
-            let mut data_type = col.data_type.clone();
-            let mut default_clause = String::new();
-
-            // Detect SERIAL/BIGSERIAL patterns to avoid "sequence does not exist" errors
-            let is_sequence = col
-                .column_default
-                .as_ref()
-                .map_or(false, |d| d.contains("nextval"));
-
-            if is_sequence {
-                if data_type.to_lowercase() == "integer" {
-                    data_type = "SERIAL".to_string();
-                } else if data_type.to_lowercase() == "bigint" {
-                    data_type = "BIGSERIAL".to_string();
-                } else if data_type.to_lowercase() == "smallint" {
-                    data_type = "SMALLSERIAL".to_string();
-                } else {
-                    // Fallback to original if we don't know the serial type
-                    if let Some(ref default) = col.column_default {
-                        default_clause = format!(" DEFAULT {}", default);
-                    }
-                }
-            } else if let Some(ref default) = col.column_default {
-                default_clause = format!(" DEFAULT {}", default);
-            }
-
-            let mut def = format!("    {} {}", quote_ident(&col.name), data_type);
-            if !col.is_nullable && !is_sequence {
-                // SERIAL implies NOT NULL
-                def.push_str(" NOT NULL");
-            }
-
-            def.push_str(&default_clause);
-            def
+            format!(
+                "    {}",
+                generate_column_definition(col, preserve_sequences)
+            )
         })
         .collect();
 
@@ -241,12 +846,685 @@ fn generate_create_table_statement(
         sql.push_str(&format!(",\n    PRIMARY KEY ({})", pk_cols.join(", ")));
     }
 
-    sql.push_str("\n);");
+    for check in check_constraints {
+        sql.push_str(&format!(
+            ",\n    CONSTRAINT {} {}",
+            quote_ident(&check.name),
+            check.definition
+        ));
+    }
+
+    sql.push_str("\n)");
+    if let Some(partition_key) = partition_key {
+        sql.push_str(&format!(" PARTITION BY {}", partition_key));
+    }
+    sql.push(';');
     sql
 }
 
+/// Column definition as used in CREATE TABLE and ALTER TABLE ... ADD COLUMN, e.g.
+/// `"id" SERIAL` or `"name" text COLLATE "C" NOT NULL DEFAULT ''`. With `preserve_sequences`
+/// nextval() defaults are kept as written instead of becoming SERIAL.
+pub fn generate_column_definition(col: &ColumnInfo, preserve_sequences: bool) -> String {
+    // information_schema reports arrays as "ARRAY"; the element type is in udt_name ("_int4")
+    let mut data_type = if col.data_type == "ARRAY" {
+        let element_type = col.udt_name.trim_start_matches('_');
+        if col.udt_schema == "pg_catalog" {
+            col.formatted_type.clone()
+        } else {
+            // Arrays of extension/enum types (citext[], ...) keep their schema
+            format!(
+                "{}.{}{}[]",
+                quote_ident(&col.udt_schema),
+                quote_ident(element_type),
+                type_modifiers(&col.formatted_type)
+            )
+        }
+    } else if col.data_type == "USER-DEFINED" {
+        // Keep modifiers such as pgvector's dimensions, e.g. vector(1536)
+        format!(
+            "{}.{}{}",
+            quote_ident(&col.udt_schema),
+            quote_ident(&col.udt_name),
+            type_modifiers(&col.formatted_type)
+        )
+    } else {
+        col.formatted_type.clone()
+    };
+    let mut default_clause = String::new();
+
+    // Detect SERIAL/BIGSERIAL patterns to avoid "sequence does not exist" errors
+    let is_sequence = !preserve_sequences
+        && col
+            .column_default
+            .as_deref()
+            .is_some_and(|d| d.contains("nextval"));
+
+    if let Some(ref expression) = col.generation_expression {
+        default_clause = format!(" GENERATED ALWAYS AS ({}) STORED", expression);
+    } else if let Some(ref generation) = col.identity_generation {
+        default_clause = format!(" GENERATED {} AS IDENTITY", generation);
+    } else if is_sequence {
+        if data_type.to_lowercase() == "integer" {
+            data_type = "SERIAL".to_string();
+        } else if data_type.to_lowercase() == "bigint" {
+            data_type = "BIGSERIAL".to_string();
+        } else if data_type.to_lowercase() == "smallint" {
+            data_type = "SMALLSERIAL".to_string();
+        } else {
+            // Fallback to original if we don't know the serial type
+            if let Some(ref default) = col.column_default {
+                default_clause = format!(" DEFAULT {}", default);
+            }
+        }
+    } else if let Some(ref default) = col.column_default {
+        default_clause = format!(" DEFAULT {}", default);
+    }
+
+    let mut def = format!("{} {}", quote_ident(&col.name), data_type);
+    if let Some(ref collation) = col.collation {
+        def.push_str(&format!(" COLLATE {}", collation));
+    }
+    if !col.is_nullable && !is_sequence {
+        // SERIAL implies NOT NULL
+        def.push_str(" NOT NULL");
+    }
+
+    def.push_str(&default_clause);
+    def
+}
+
+/// Type modifiers of a formatted type, e.g. "(1536)" for "vector(1536)" or "vector(3)[]"
+fn type_modifiers(formatted_type: &str) -> &str {
+    let base = formatted_type.trim_end_matches("[]");
+    match base.rfind('(') {
+        Some(start) if base.ends_with(')') => &base[start..],
+        _ => "",
+    }
+}
+
+/// Point `nextval('schema.seq')` in a column default at `target_schema`, for tables moved
+/// to another schema. Unqualified sequence names are left alone.
+pub fn retarget_sequence_default(
+    default: &str,
+    source_schema: &str,
+    target_schema: &str,
+) -> String {
+    let target = format!(
+        "nextval('{}.",
+        quote_ident(target_schema).replace('\'', "''")
+    );
+    [source_schema.to_string(), quote_ident(source_schema)]
+        .iter()
+        .fold(default.to_string(), |default, source| {
+            default.replace(
+                &format!("nextval('{}.", source.replace('\'', "''")),
+                &target,
+            )
+        })
+}
+
+/// Get all partitions of a partitioned table, recursing into sub-partitions
+pub async fn get_table_partitions(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<PartitionInfo>, MigrateError> {
+    let query = r#"
+        WITH RECURSIVE tree AS (
+            SELECT i.inhrelid AS relid, i.inhparent AS parent_oid, 1 AS level
+            FROM pg_inherits i
+            JOIN pg_class p ON p.oid = i.inhparent
+            JOIN pg_namespace n ON n.oid = p.relnamespace
+            WHERE n.nspname = $1 AND p.relname = $2
+            UNION ALL
+            SELECT i.inhrelid, i.inhparent, t.level + 1
+            FROM pg_inherits i
+            JOIN tree t ON i.inhparent = t.relid
+        )
+        SELECT
+            cn.nspname::text AS partition_schema,
+            c.relname::text AS partition_name,
+            pn.nspname::text AS parent_schema,
+            p.relname::text AS parent_name,
+            pg_get_expr(c.relpartbound, c.oid) AS bound,
+            CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END AS partition_key
+        FROM tree t
+        JOIN pg_class c ON c.oid = t.relid
+        JOIN pg_namespace cn ON cn.oid = c.relnamespace
+        JOIN pg_class p ON p.oid = t.parent_oid
+        JOIN pg_namespace pn ON pn.oid = p.relnamespace
+        WHERE c.relispartition
+        ORDER BY t.level, cn.nspname, c.relname
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get partitions", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| PartitionInfo {
+            schema: row.get("partition_schema"),
+            name: row.get("partition_name"),
+            parent_schema: row.get("parent_schema"),
+            parent_name: row.get("parent_name"),
+            bound: row.get("bound"),
+            partition_key: row.get("partition_key"),
+        })
+        .collect())
+}
+
+/// Generate a CREATE TABLE ... PARTITION OF statement for a partition
+pub fn generate_create_partition_statement(
+    partition: &PartitionInfo,
+    schema: &str,
+    parent_schema: &str,
+) -> String {
+    let mut sql = format!(
+        "CREATE TABLE IF NOT EXISTS {}.{} PARTITION OF {}.{} {}",
+        quote_ident(schema),
+        quote_ident(&partition.name),
+        quote_ident(parent_schema),
+        quote_ident(&partition.parent_name),
+        partition.bound
+    );
+    if let Some(ref partition_key) = partition.partition_key {
+        sql.push_str(&format!(" PARTITION BY {}", partition_key));
+    }
+    sql.push(';');
+    sql
+}
+
+/// Types of a table's columns (element types for arrays), and of the fields of composite
+/// types among them, recursively; `depth` is 0 for the columns' own types
+const USED_TYPES_CTE: &str = r#"
+        WITH RECURSIVE used_types(type_oid, depth) AS (
+            SELECT DISTINCT
+                CASE WHEN t.typcategory = 'A' THEN t.typelem ELSE t.oid END,
+                0
+            FROM pg_attribute a
+            JOIN pg_class c ON c.oid = a.attrelid
+            JOIN pg_namespace cn ON cn.oid = c.relnamespace
+            JOIN pg_type t ON t.oid = a.atttypid
+            WHERE cn.nspname = $1
+                AND c.relname = $2
+                AND a.attnum > 0
+                AND NOT a.attisdropped
+            UNION
+            SELECT
+                CASE WHEN t.typcategory = 'A' THEN t.typelem ELSE t.oid END,
+                u.depth + 1
+            FROM used_types u
+            JOIN pg_type ct ON ct.oid = u.type_oid
+            JOIN pg_class cc ON cc.oid = ct.typrelid AND cc.relkind = 'c'
+            JOIN pg_attribute a ON a.attrelid = cc.oid AND a.attnum > 0 AND NOT a.attisdropped
+            JOIN pg_type t ON t.oid = a.atttypid
+            WHERE u.depth < 16
+        )
+"#;
+
+/// Get enum types used by a table's columns (including arrays of enums and enums inside
+/// composite types)
+pub async fn get_table_enums(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<EnumType>, MigrateError> {
+    let query = format!(
+        r#"
+        {}
+        SELECT
+            n.nspname::text AS type_schema,
+            t.typname::text AS type_name,
+            array_agg(e.enumlabel::text ORDER BY e.enumsortorder) AS labels
+        FROM (SELECT DISTINCT type_oid FROM used_types) u
+        JOIN pg_type t ON t.oid = u.type_oid
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        JOIN pg_enum e ON e.enumtypid = t.oid
+        GROUP BY n.nspname, t.typname
+        ORDER BY n.nspname, t.typname
+    "#,
+        USED_TYPES_CTE
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get enum types", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| EnumType {
+            schema: row.get("type_schema"),
+            name: row.get("type_name"),
+            labels: row.get("labels"),
+        })
+        .collect())
+}
+
+/// Get composite types used by a table's columns (including arrays and composites nested in
+/// them), ordered so that a type comes after the composites it contains
+pub async fn get_table_composites(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<CompositeType>, MigrateError> {
+    // Only standalone composites (relkind 'c'); a table's row type exists with the table
+    let query = format!(
+        r#"
+        {}
+        SELECT
+            n.nspname::text AS type_schema,
+            t.typname::text AS type_name,
+            array_agg(a.attname::text ORDER BY a.attnum) AS field_names,
+            array_agg(format_type(a.atttypid, a.atttypmod) ORDER BY a.attnum) AS field_types
+        FROM (SELECT type_oid, max(depth) AS depth FROM used_types GROUP BY type_oid) u
+        JOIN pg_type t ON t.oid = u.type_oid
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        JOIN pg_class c ON c.oid = t.typrelid AND c.relkind = 'c'
+        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+        GROUP BY n.nspname, t.typname, u.depth
+        ORDER BY u.depth DESC, n.nspname, t.typname
+    "#,
+        USED_TYPES_CTE
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get composite types", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let names: Vec<String> = row.get("field_names");
+            let types: Vec<String> = row.get("field_types");
+            CompositeType {
+                schema: row.get("type_schema"),
+                name: row.get("type_name"),
+                fields: names
+                    .into_iter()
+                    .zip(types)
+                    .map(|(name, data_type)| CompositeField { name, data_type })
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
+/// Get extensions that provide types used by a table's columns (including arrays)
+pub async fn get_table_extensions(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<ExtensionInfo>, MigrateError> {
+    let query = r#"
+        WITH used_types AS (
+            SELECT DISTINCT
+                CASE WHEN t.typcategory = 'A' THEN t.typelem ELSE t.oid END AS type_oid
+            FROM pg_attribute a
+            JOIN pg_class c ON c.oid = a.attrelid
+            JOIN pg_namespace cn ON cn.oid = c.relnamespace
+            JOIN pg_type t ON t.oid = a.atttypid
+            WHERE cn.nspname = $1
+                AND c.relname = $2
+                AND a.attnum > 0
+                AND NOT a.attisdropped
+        )
+        SELECT DISTINCT
+            e.extname::text AS extension_name,
+            n.nspname::text AS extension_schema
+        FROM used_types u
+        JOIN pg_depend d ON d.classid = 'pg_type'::regclass
+            AND d.objid = u.type_oid
+            AND d.deptype = 'e'
+        JOIN pg_extension e ON e.oid = d.refobjid
+        JOIN pg_namespace n ON n.oid = e.extnamespace
+        ORDER BY 1
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get extensions", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ExtensionInfo {
+            name: row.get("extension_name"),
+            schema: row.get("extension_schema"),
+        })
+        .collect())
+}
+
+/// Generate a CREATE EXTENSION statement that installs into the source's schema
+pub fn generate_create_extension_statement(extension: &ExtensionInfo) -> String {
+    format!(
+        "CREATE EXTENSION IF NOT EXISTS {} SCHEMA {}",
+        quote_ident(&extension.name),
+        quote_ident(&extension.schema)
+    )
+}
+
+/// Generate an idempotent CREATE TYPE ... AS ENUM statement
+pub fn generate_create_enum_statement(enum_type: &EnumType) -> String {
+    let labels: Vec<String> = enum_type.labels.iter().map(|l| quote_literal(l)).collect();
+    format!(
+        "DO $pgm$ BEGIN\n    CREATE TYPE {}.{} AS ENUM ({});\nEXCEPTION WHEN duplicate_object THEN NULL;\nEND $pgm$;",
+        quote_ident(&enum_type.schema),
+        quote_ident(&enum_type.name),
+        labels.join(", ")
+    )
+}
+
+/// Generate CREATE TYPE ... AS (...) for a composite type, skipped if it already exists
+pub fn generate_create_composite_statement(composite: &CompositeType) -> String {
+    let fields: Vec<String> = composite
+        .fields
+        .iter()
+        .map(|f| format!("{} {}", quote_ident(&f.name), f.data_type))
+        .collect();
+    format!(
+        "DO $pgm$ BEGIN\n    CREATE TYPE {}.{} AS ({});\nEXCEPTION WHEN duplicate_object THEN NULL;\nEND $pgm$;",
+        quote_ident(&composite.schema),
+        quote_ident(&composite.name),
+        fields.join(", ")
+    )
+}
+
+/// Get the table comment and any column comments
+pub async fn get_comments(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<TableComments, MigrateError> {
+    let table_query = r#"
+        SELECT obj_description(c.oid, 'pg_class') AS comment
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+    "#;
+
+    let table_comment: Option<String> = sqlx::query_scalar::<_, Option<String>>(table_query)
+        .bind(schema)
+        .bind(table)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get table comment", e))?
+        .flatten();
+
+    let columns_query = r#"
+        SELECT
+            a.attname::text AS column_name,
+            col_description(c.oid, a.attnum) AS comment
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND a.attnum > 0
+            AND NOT a.attisdropped
+            AND col_description(c.oid, a.attnum) IS NOT NULL
+        ORDER BY a.attnum
+    "#;
+
+    let rows = sqlx::query(columns_query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get column comments", e))?;
+
+    Ok(TableComments {
+        table: table_comment,
+        columns: rows
+            .iter()
+            .map(|row| (row.get("column_name"), row.get("comment")))
+            .collect(),
+    })
+}
+
+/// Generate COMMENT ON TABLE / COMMENT ON COLUMN statements
+pub fn generate_comment_statements(
+    comments: &TableComments,
+    schema: &str,
+    table: &str,
+) -> Vec<String> {
+    let full_table = format!("{}.{}", quote_ident(schema), quote_ident(table));
+    let mut statements = Vec::new();
+
+    if let Some(ref comment) = comments.table {
+        statements.push(format!(
+            "COMMENT ON TABLE {} IS {}",
+            full_table,
+            quote_literal(comment)
+        ));
+    }
+    for (column, comment) in &comments.columns {
+        statements.push(format!(
+            "COMMENT ON COLUMN {}.{} IS {}",
+            full_table,
+            quote_ident(column),
+            quote_literal(comment)
+        ));
+    }
+
+    statements
+}
+
+/// Get sequences in a schema that aren't owned by a table column (serial/identity
+/// sequences are kept in step by the table migration instead)
+pub async fn get_standalone_sequences(
+    pool: &PgPool,
+    schema: &str,
+) -> Result<Vec<SequenceInfo>, MigrateError> {
+    let query = r#"
+        SELECT
+            s.sequencename::text AS name,
+            s.data_type::text AS data_type,
+            s.start_value,
+            s.min_value,
+            s.max_value,
+            s.increment_by,
+            s.cycle,
+            s.cache_size,
+            s.last_value
+        FROM pg_sequences s
+        WHERE s.schemaname = $1
+            AND NOT EXISTS (
+                SELECT 1
+                FROM pg_depend d
+                WHERE d.classid = 'pg_class'::regclass
+                    AND d.objid = format('%I.%I', s.schemaname, s.sequencename)::regclass
+                    AND d.deptype IN ('a', 'i')
+            )
+        ORDER BY s.sequencename
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get sequences", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| SequenceInfo {
+            name: row.get("name"),
+            data_type: row.get("data_type"),
+            start_value: row.get("start_value"),
+            min_value: row.get("min_value"),
+            max_value: row.get("max_value"),
+            increment_by: row.get("increment_by"),
+            cycle: row.get("cycle"),
+            cache_size: row.get("cache_size"),
+            last_value: row.get("last_value"),
+        })
+        .collect())
+}
+
+/// Sequence owned by a column of a table (SERIAL or identity)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedSequence {
+    pub column: String,
+    pub schema: String,
+    pub sequence: SequenceInfo,
+}
+
+/// Get the sequences owned by a table's columns
+pub async fn get_owned_sequences(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<OwnedSequence>, MigrateError> {
+    let query = r#"
+        SELECT
+            a.attname::text AS column_name,
+            s.schemaname::text AS schema_name,
+            s.sequencename::text AS name,
+            s.data_type::text AS data_type,
+            s.start_value,
+            s.min_value,
+            s.max_value,
+            s.increment_by,
+            s.cycle,
+            s.cache_size,
+            s.last_value
+        FROM pg_sequences s
+        JOIN pg_depend d ON d.classid = 'pg_class'::regclass
+            AND d.objid = format('%I.%I', s.schemaname, s.sequencename)::regclass
+            AND d.deptype IN ('a', 'i')
+        JOIN pg_class t ON t.oid = d.refobjid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = d.refobjsubid
+        WHERE n.nspname = $1
+            AND t.relname = $2
+        ORDER BY a.attnum
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get owned sequences", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| OwnedSequence {
+            column: row.get("column_name"),
+            schema: row.get("schema_name"),
+            sequence: SequenceInfo {
+                name: row.get("name"),
+                data_type: row.get("data_type"),
+                start_value: row.get("start_value"),
+                min_value: row.get("min_value"),
+                max_value: row.get("max_value"),
+                increment_by: row.get("increment_by"),
+                cycle: row.get("cycle"),
+                cache_size: row.get("cache_size"),
+                last_value: row.get("last_value"),
+            },
+        })
+        .collect())
+}
+
+/// Generate a CREATE SEQUENCE IF NOT EXISTS statement for a server of `server_version`
+/// (`server_version_num`). Sequences have a data type since PostgreSQL 10; before that they
+/// are always bigint, bounded by MINVALUE/MAXVALUE.
+pub fn generate_create_sequence_statement(
+    sequence: &SequenceInfo,
+    schema: &str,
+    server_version: i32,
+) -> String {
+    let data_type = if server_version >= 100000 {
+        format!(" AS {}", sequence.data_type)
+    } else {
+        String::new()
+    };
+    format!(
+        "CREATE SEQUENCE IF NOT EXISTS {}.{}{} INCREMENT BY {} MINVALUE {} MAXVALUE {} START WITH {} CACHE {}{}",
+        quote_ident(schema),
+        quote_ident(&sequence.name),
+        data_type,
+        sequence.increment_by,
+        sequence.min_value,
+        sequence.max_value,
+        sequence.start_value,
+        sequence.cache_size,
+        if sequence.cycle { " CYCLE" } else { " NO CYCLE" }
+    )
+}
+
+/// Get all non primary key indexes on a table
+pub async fn get_table_indexes(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<IndexInfo>, MigrateError> {
+    let query = r#"
+        SELECT
+            i.relname::text AS index_name,
+            ix.indisunique AS is_unique,
+            pg_get_indexdef(ix.indexrelid) AS definition
+        FROM pg_index ix
+        JOIN pg_class i ON i.oid = ix.indexrelid
+        JOIN pg_class t ON t.oid = ix.indrelid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        WHERE n.nspname = $1
+            AND t.relname = $2
+            AND NOT ix.indisprimary
+        ORDER BY i.relname
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| MigrateError::sql("Failed to get indexes", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| IndexInfo {
+            name: row.get("index_name"),
+            is_unique: row.get("is_unique"),
+            definition: row.get("definition"),
+        })
+        .collect())
+}
+
+/// Generate a CREATE INDEX IF NOT EXISTS statement for an index on the given target table
+pub fn generate_create_index_statement(index: &IndexInfo, schema: &str, table: &str) -> String {
+    // Keep everything from "USING method (...)" onwards (columns, INCLUDE, WHERE predicate)
+    let using_clause = index
+        .definition
+        .find(" USING ")
+        .map(|pos| &index.definition[pos + 1..])
+        .unwrap_or("");
+
+    format!(
+        "CREATE {}INDEX IF NOT EXISTS {} ON {}.{} {}",
+        if index.is_unique { "UNIQUE " } else { "" },
+        quote_ident(&index.name),
+        quote_ident(schema),
+        quote_ident(table),
+        using_clause
+    )
+}
+
 /// List all schemas in the database (excluding system schemas)
-pub async fn list_schemas(pool: &PgPool) -> Result<Vec<String>, String> {
+pub async fn list_schemas(pool: &PgPool) -> Result<Vec<String>, MigrateError> {
     let query = r#"
         SELECT schema_name 
         FROM information_schema.schemata 
@@ -259,18 +1537,23 @@ pub async fn list_schemas(pool: &PgPool) -> Result<Vec<String>, String> {
     let rows = sqlx::query(query)
         .fetch_all(pool)
         .await
-        .map_err(|e| format!("Failed to list schemas: {}", e))?;
+        .map_err(|e| MigrateError::sql("Failed to list schemas", e))?;
 
     Ok(rows.iter().map(|r| r.get("schema_name")).collect())
 }
 
 /// Quote an identifier for PostgreSQL
-fn quote_ident(name: &str) -> String {
+pub fn quote_ident(name: &str) -> String {
     format!("\"{}\"", name.replace('"', "\"\""))
 }
 
+/// Quote a string literal for PostgreSQL
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
 /// Get all table dependencies (Foreign Keys)
-pub async fn get_all_dependencies(pool: &PgPool) -> Result<Vec<TableDependency>, String> {
+pub async fn get_all_dependencies(pool: &PgPool) -> Result<Vec<TableDependency>, MigrateError> {
     let query = r#"
         SELECT
             tc.table_schema,
@@ -291,7 +1574,7 @@ pub async fn get_all_dependencies(pool: &PgPool) -> Result<Vec<TableDependency>,
     let rows = sqlx::query(query)
         .fetch_all(pool)
         .await
-        .map_err(|e| format!("Failed to get dependencies: {}", e))?;
+        .map_err(|e| MigrateError::sql("Failed to get dependencies", e))?;
 
     let mut map: std::collections::HashMap<(String, String), Vec<(String, String)>> =
         std::collections::HashMap::new();
@@ -322,3 +1605,216 @@ pub async fn get_all_dependencies(pool: &PgPool) -> Result<Vec<TableDependency>,
 
     Ok(dependencies)
 }
+
+/// Order of `tables` (as indices into it) in which every table comes after the tables it
+/// references. Independent tables stay alphabetical; a cycle is broken at an arbitrary table.
+/// A table listed more than once keeps every entry, next to each other.
+pub fn dependency_order(
+    tables: &[(String, String)],
+    dependencies: &[TableDependency],
+) -> Vec<usize> {
+    let mut positions: HashMap<&(String, String), Vec<usize>> = HashMap::new();
+    for (i, table) in tables.iter().enumerate() {
+        positions.entry(table).or_default().push(i);
+    }
+    // Each table is ordered by its first entry
+    let index: HashMap<&(String, String), usize> = positions
+        .iter()
+        .map(|(&table, entries)| (table, entries[0]))
+        .collect();
+
+    // Edges from each table to the tables it references, within `tables` only
+    let mut parents: HashMap<usize, Vec<usize>> = HashMap::new();
+    for dep in dependencies {
+        let Some(&child) = index.get(&(dep.schema.clone(), dep.name.clone())) else {
+            continue;
+        };
+        for parent in &dep.depends_on {
+            if let Some(&parent) = index.get(parent) {
+                parents.entry(child).or_default().push(parent);
+            }
+        }
+    }
+
+    // Post-order DFS: a table is emitted after all of its parents
+    fn visit(
+        node: usize,
+        parents: &HashMap<usize, Vec<usize>>,
+        visited: &mut HashSet<usize>,
+        in_progress: &mut HashSet<usize>,
+        order: &mut Vec<usize>,
+    ) {
+        if visited.contains(&node) || !in_progress.insert(node) {
+            // Done already, or a cycle back to a table being visited
+            return;
+        }
+        for &parent in parents.get(&node).into_iter().flatten() {
+            visit(parent, parents, visited, in_progress, order);
+        }
+        in_progress.remove(&node);
+        visited.insert(node);
+        order.push(node);
+    }
+
+    let mut nodes: Vec<usize> = index.values().copied().collect();
+    nodes.sort_by_key(|&i| &tables[i]);
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    let mut in_progress = HashSet::new();
+    for node in nodes {
+        visit(node, &parents, &mut visited, &mut in_progress, &mut order);
+    }
+    order
+        .into_iter()
+        .flat_map(|node| positions[&tables[node]].iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> (String, String) {
+        ("public".to_string(), name.to_string())
+    }
+
+    fn depends(name: &str, parents: &[&str]) -> TableDependency {
+        TableDependency {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            depends_on: parents.iter().map(|p| key(p)).collect(),
+        }
+    }
+
+    #[test]
+    fn dependency_order_puts_parents_first() {
+        let tables = [key("orders"), key("customers"), key("audit")];
+        let dependencies = [depends("orders", &["customers"])];
+        assert_eq!(dependency_order(&tables, &dependencies), [2, 1, 0]);
+    }
+
+    #[test]
+    fn dependency_order_keeps_duplicate_selections() {
+        // e.g. `customers` copied to both `customers` and `customers_backup`
+        let tables = [key("orders"), key("customers"), key("customers")];
+        let dependencies = [depends("orders", &["customers"])];
+        assert_eq!(dependency_order(&tables, &dependencies), [1, 2, 0]);
+    }
+
+    #[test]
+    fn retarget_sequence_default_leaves_unqualified_names() {
+        let default = "nextval('users_id_seq'::regclass)";
+        assert_eq!(
+            retarget_sequence_default(default, "public", "archive"),
+            default
+        );
+    }
+
+    #[test]
+    fn retarget_sequence_default_moves_qualified_names() {
+        assert_eq!(
+            retarget_sequence_default(
+                "nextval('public.users_id_seq'::regclass)",
+                "public",
+                "archive"
+            ),
+            "nextval('\"archive\".users_id_seq'::regclass)"
+        );
+        assert_eq!(
+            retarget_sequence_default(
+                "nextval('\"Sales Data\".orders_id_seq'::regclass)",
+                "Sales Data",
+                "O'Brien"
+            ),
+            "nextval('\"O''Brien\".orders_id_seq'::regclass)"
+        );
+    }
+
+    #[test]
+    fn create_sequence_has_a_type_from_postgres_10() {
+        let sequence = SequenceInfo {
+            name: "Order Ids".to_string(),
+            data_type: "integer".to_string(),
+            start_value: 1,
+            min_value: 1,
+            max_value: 2147483647,
+            increment_by: 1,
+            cycle: false,
+            cache_size: 1,
+            last_value: None,
+        };
+        assert_eq!(
+            generate_create_sequence_statement(&sequence, "sales", 160000),
+            "CREATE SEQUENCE IF NOT EXISTS \"sales\".\"Order Ids\" AS integer INCREMENT BY 1 \
+             MINVALUE 1 MAXVALUE 2147483647 START WITH 1 CACHE 1 NO CYCLE"
+        );
+        assert_eq!(
+            generate_create_sequence_statement(&sequence, "sales", 90600),
+            "CREATE SEQUENCE IF NOT EXISTS \"sales\".\"Order Ids\" INCREMENT BY 1 \
+             MINVALUE 1 MAXVALUE 2147483647 START WITH 1 CACHE 1 NO CYCLE"
+        );
+    }
+
+    fn user_column(name: &str, data_type: &str, udt_name: &str, formatted: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            udt_schema: "public".to_string(),
+            udt_name: udt_name.to_string(),
+            is_nullable: true,
+            column_default: None,
+            is_primary_key: false,
+            ordinal_position: 1,
+            identity_generation: None,
+            generation_expression: None,
+            formatted_type: formatted.to_string(),
+            collation: None,
+        }
+    }
+
+    #[test]
+    fn type_modifiers_of_formatted_types() {
+        assert_eq!(type_modifiers("vector(1536)"), "(1536)");
+        assert_eq!(type_modifiers("vector(3)[]"), "(3)");
+        assert_eq!(type_modifiers("numeric(10,2)"), "(10,2)");
+        assert_eq!(type_modifiers("citext[]"), "");
+        assert_eq!(type_modifiers("text"), "");
+    }
+
+    #[test]
+    fn extension_types_keep_modifiers_and_schema() {
+        let embedding = user_column("embedding", "USER-DEFINED", "vector", "vector(1536)");
+        assert_eq!(
+            generate_column_definition(&embedding, false),
+            "\"embedding\" \"public\".\"vector\"(1536)"
+        );
+
+        let points = user_column("points", "ARRAY", "_vector", "vector(3)[]");
+        assert_eq!(
+            generate_column_definition(&points, false),
+            "\"points\" \"public\".\"vector\"(3)[]"
+        );
+
+        let tags = ColumnInfo {
+            is_nullable: false,
+            ..user_column("tags", "ARRAY", "_citext", "citext[]")
+        };
+        assert_eq!(
+            generate_column_definition(&tags, false),
+            "\"tags\" \"public\".\"citext\"[] NOT NULL"
+        );
+    }
+
+    #[test]
+    fn builtin_arrays_keep_their_formatted_type() {
+        let scores = ColumnInfo {
+            udt_schema: "pg_catalog".to_string(),
+            ..user_column("scores", "ARRAY", "_int4", "integer[]")
+        };
+        assert_eq!(
+            generate_column_definition(&scores, false),
+            "\"scores\" integer[]"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use async_trait::async_trait;
+use russh::client;
+use russh_keys::key::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// SSH bastion used to reach a database that isn't directly accessible
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshTunnelConfig {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    pub username: String,
+    pub private_key_path: String,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Trust a bastion missing from ~/.ssh/known_hosts and remember its key. Otherwise an
+    /// unknown host is rejected with its fingerprint so it can be checked first.
+    #[serde(default)]
+    pub accept_new_host_key: bool,
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+/// Why the bastion's host key was rejected
+#[derive(Debug, Clone)]
+enum HostKeyRejection {
+    /// Not in known_hosts, and new keys aren't accepted
+    Unknown { fingerprint: String },
+    /// Differs from the key recorded on `line` of known_hosts
+    Changed { fingerprint: String, line: usize },
+    /// known_hosts couldn't be read
+    Unreadable { error: String },
+}
+
+/// Outcome of the host key check, read back once the handshake is over
+#[derive(Debug, Default)]
+struct HostKeyState {
+    rejection: Option<HostKeyRejection>,
+    /// An accepted new key couldn't be written to known_hosts
+    learn_error: Option<String>,
+}
+
+/// Verifies the bastion's host key against ~/.ssh/known_hosts
+struct TunnelClient {
+    host: String,
+    port: u16,
+    accept_new_host_key: bool,
+    state: Arc<Mutex<HostKeyState>>,
+}
+
+impl TunnelClient {
+    fn reject(&self, rejection: HostKeyRejection) -> Result<bool, russh::Error> {
+        if let Ok(mut state) = self.state.lock() {
+            state.rejection = Some(rejection);
+        }
+        Ok(false)
+    }
+}
+
+#[async_trait]
+impl client::Handler for TunnelClient {
+    type Error = russh::Error;
+
+    async fn check_server_key(
+        &mut self,
+        server_public_key: &PublicKey,
+    ) -> Result<bool, Self::Error> {
+        // A changed key is always rejected; an unknown host only when not opted in
+        match russh_keys::check_known_hosts(&self.host, self.port, server_public_key) {
+            Ok(true) => Ok(true),
+            Ok(false) if self.accept_new_host_key => {
+                // The key is trusted for this session either way; failing to remember it
+                // only means it is asked about again next time
+                if let Err(e) =
+                    russh_keys::learn_known_hosts(&self.host, self.port, server_public_key)
+                {
+                    if let Ok(mut state) = self.state.lock() {
+                        state.learn_error = Some(e.to_string());
+                    }
+                }
+                Ok(true)
+            }
+            Ok(false) => self.reject(HostKeyRejection::Unknown {
+                fingerprint: server_public_key.fingerprint(),
+            }),
+            Err(russh_keys::Error::KeyChanged { line }) => self.reject(HostKeyRejection::Changed {
+                fingerprint: server_public_key.fingerprint(),
+                line,
+            }),
+            Err(e) => self.reject(HostKeyRejection::Unreadable {
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// Error for a connection refused because of the bastion's host key
+fn rejection_message(host: &str, port: u16, rejection: &HostKeyRejection) -> String {
+    match rejection {
+        HostKeyRejection::Unknown { fingerprint } => format!(
+            "Unknown SSH host key for {}:{} (SHA256:{}). Verify the fingerprint, then \
+             enable accept_new_host_key to trust it",
+            host, port, fingerprint
+        ),
+        HostKeyRejection::Changed { fingerprint, line } => format!(
+            "SSH host key for {}:{} has changed (now SHA256:{}) and doesn't match line {} of \
+             ~/.ssh/known_hosts. If the change is expected, remove that line and connect again",
+            host, port, fingerprint, line
+        ),
+        HostKeyRejection::Unreadable { error } => format!(
+            "Couldn't check the SSH host key for {}:{}: failed to read ~/.ssh/known_hosts: {}",
+            host, port, error
+        ),
+    }
+}
+
+/// Local port forwarded through an SSH session; closed when dropped
+pub struct SshTunnel {
+    pub local_port: u16,
+    /// Non-fatal problem while opening the tunnel, e.g. a new host key that couldn't be saved
+    pub warning: Option<String>,
+    task: JoinHandle<()>,
+}
+
+impl SshTunnel {
+    /// Connect to the bastion and forward a local port to `remote_host:remote_port`
+    pub async fn open(
+        config: &SshTunnelConfig,
+        remote_host: &str,
+        remote_port: u16,
+    ) -> Result<SshTunnel, String> {
+        let key =
+            russh_keys::load_secret_key(&config.private_key_path, config.passphrase.as_deref())
+                .map_err(|e| format!("Failed to load SSH key: {}", e))?;
+
+        let state = Arc::new(Mutex::new(HostKeyState::default()));
+        let handler = TunnelClient {
+            host: config.host.clone(),
+            port: config.port,
+            accept_new_host_key: config.accept_new_host_key,
+            state: state.clone(),
+        };
+        let mut session = client::connect(
+            Arc::new(client::Config::default()),
+            (config.host.as_str(), config.port),
+            handler,
+        )
+        .await
+        .map_err(
+            |e| match state.lock().ok().and_then(|s| s.rejection.clone()) {
+                Some(rejection) => rejection_message(&config.host, config.port, &rejection),
+                None => format!("Failed to connect to SSH host: {}", e),
+            },
+        )?;
+        let warning = state
+            .lock()
+            .ok()
+            .and_then(|s| s.learn_error.clone())
+            .map(|e| {
+                format!(
+                    "Trusted the new SSH host key for {}:{}, but couldn't save it to \
+                     ~/.ssh/known_hosts: {}",
+                    config.host, config.port, e
+                )
+            });
+
+        let authenticated = session
+            .authenticate_publickey(&config.username, Arc::new(key))
+            .await
+            .map_err(|e| format!("SSH authentication failed: {}", e))?;
+        if !authenticated {
+            return Err("SSH authentication failed: key rejected".to_string());
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| format!("Failed to open local tunnel port: {}", e))?;
+        let local_port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to open local tunnel port: {}", e))?
+            .port();
+
+        // Forward every local connection over its own direct-tcpip channel
+        let session = Arc::new(session);
+        let remote_host = remote_host.to_string();
+        let task = tokio::spawn(async move {
+            while let Ok((mut socket, addr)) = listener.accept().await {
+                let session = session.clone();
+                let remote_host = remote_host.clone();
+                tokio::spawn(async move {
+                    let channel = match session
+                        .channel_open_direct_tcpip(
+                            remote_host,
+                            remote_port as u32,
+                            addr.ip().to_string(),
+                            addr.port() as u32,
+                        )
+                        .await
+                    {
+                        Ok(channel) => channel,
+                        Err(_) => return,
+                    };
+                    let mut stream = channel.into_stream();
+                    let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                });
+            }
+        });
+
+        Ok(SshTunnel {
+            local_port,
+            warning,
+            task,
+        })
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_host_key_is_reported_with_its_line() {
+        let message = rejection_message(
+            "bastion",
+            2222,
+            &HostKeyRejection::Changed {
+                fingerprint: "abc".to_string(),
+                line: 7,
+            },
+        );
+        assert!(message.contains("bastion:2222 has changed"), "{message}");
+        assert!(message.contains("SHA256:abc"), "{message}");
+        assert!(message.contains("line 7"), "{message}");
+    }
+
+    #[test]
+    fn unknown_host_key_points_to_accept_new_host_key() {
+        let message = rejection_message(
+            "bastion",
+            22,
+            &HostKeyRejection::Unknown {
+                fingerprint: "abc".to_string(),
+            },
+        );
+        assert!(message.starts_with("Unknown SSH host key for bastion:22 (SHA256:abc)"));
+        assert!(message.contains("accept_new_host_key"));
+    }
+}
@@ -3,9 +3,16 @@ mod db;
 
 use std::sync::Arc;
 
+use tauri::{Manager, RunEvent};
+
 use commands::{
-    cancel_migration, connect_database, disconnect_database, get_schemas, get_table_schema,
-    get_tables, start_migration, test_connection, AppState,
+    analyze_schema, cancel_migration, check_target_readiness, connect_database,
+    connect_database_service, connect_database_url, disconnect_all, disconnect_database,
+    estimate_migration, export_tables, get_column_distinct_values, get_connections,
+    get_database_summary, get_schemas, get_table_row_count, get_table_schema, get_tables,
+    migrate_missing_tables, pause_migration, preview_table, reconnect_database, resume_migration,
+    select_schema_tables, sort_tables_by_dependency, start_migration, test_connection,
+    verify_migration, AppState,
 };
 use db::create_connection_manager;
 
@@ -19,14 +26,40 @@ pub fn run() {
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             connect_database,
+            connect_database_url,
+            connect_database_service,
             disconnect_database,
+            disconnect_all,
+            reconnect_database,
+            get_connections,
             get_tables,
+            select_schema_tables,
+            get_table_row_count,
             get_schemas,
+            get_database_summary,
             get_table_schema,
+            preview_table,
+            get_column_distinct_values,
             start_migration,
+            pause_migration,
+            resume_migration,
             cancel_migration,
+            verify_migration,
+            analyze_schema,
+            migrate_missing_tables,
+            sort_tables_by_dependency,
+            estimate_migration,
+            check_target_readiness,
+            export_tables,
             test_connection,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Close the pools on exit so the servers don't keep idle sessions around
+            if let RunEvent::Exit = event {
+                let state = app_handle.state::<Arc<AppState>>();
+                tauri::async_runtime::block_on(state.conn_manager.disconnect_all());
+            }
+        });
 }
@@ -1,4 +1,4 @@
-import { useState, useEffect } from "react";
+import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import "./index.css";
@@ -15,6 +15,22 @@ export interface ConnectionConfig {
   database: string;
   username: string;
   password: string;
+  read_only?: boolean;
+}
+
+// Error returned by every backend command
+export interface MigrateError {
+  kind: "connection" | "permission" | "unsupported_type" | "sql" | "cancelled" | "other";
+  message: string;
+  column?: string;
+  data_type?: string;
+}
+
+export function errorMessage(error: unknown): string {
+  if (error && typeof error === "object" && "message" in error) {
+    return String((error as MigrateError).message);
+  }
+  return String(error);
 }
 
 export interface ConnectionStatus {
@@ -23,21 +39,28 @@ export interface ConnectionStatus {
   database: string;
   host: string;
   error?: string;
+  server_version: string;
+  current_user: string;
+  server_version_num: number;
+  read_only: boolean;
+  warning?: string;
 }
 
 export interface TableInfo {
   name: string;
   schema: string;
   row_count: number;
+  row_count_estimated: boolean;
   size_bytes: number;
-  status?: "MATCH" | "MISSING_IN_TARGET" | "COLUMNS_MISMATCH" | "ERROR";
+  relation_kind: "table" | "partitioned_table" | "foreign_table" | "materialized_view";
+  status?: "MATCH" | "WARN" | "MISSING_IN_TARGET" | "COLUMNS_MISMATCH" | "ERROR";
   statusDetails?: string;
 }
 
 export interface SchemaDiff {
   schema: string;
   table: string;
-  status: "MATCH" | "MISSING_IN_TARGET" | "COLUMNS_MISMATCH" | "ERROR";
+  status: "MATCH" | "WARN" | "MISSING_IN_TARGET" | "COLUMNS_MISMATCH" | "ERROR";
   details?: string;
 }
 
@@ -54,8 +77,24 @@ export interface MigrationProgress {
 export interface MigrationOptions {
   create_table_if_not_exists: boolean;
   truncate_before_insert: boolean;
+  recreate_table: boolean;
   disable_constraints: boolean;
   batch_size: number;
+  confirm_destructive?: boolean;
+}
+
+// Emitted when TRUNCATE/DROP ... CASCADE would reach objects outside the selection
+export interface MigrationWarning {
+  message: string;
+  tables: string[];
+}
+
+export interface TableResult {
+  schema: string;
+  table: string;
+  rows: number;
+  elapsed_ms: number;
+  error: string | null;
 }
 
 export interface MigrationResult {
@@ -64,6 +103,8 @@ export interface MigrationResult {
   total_rows: number;
   errors: string[];
   elapsed_ms: number;
+  table_results: TableResult[];
+  migration_id: string;
 }
 
 export interface SavedConnection {
@@ -138,12 +179,17 @@ function App() {
   const [isMigrating, setIsMigrating] = useState(false);
   const [progress, setProgress] = useState<MigrationProgress | null>(null);
   const [lastResult, setLastResult] = useState<MigrationResult | null>(null);
+  const [migrationId, setMigrationId] = useState<string | null>(null);
+  const [isPaused, setIsPaused] = useState(false);
   const [targetSchema, setTargetSchema] = useState("");
+  const [destructiveWarnings, setDestructiveWarnings] = useState<MigrationWarning[]>([]);
+  const warningsRef = useRef<MigrationWarning[]>([]);
 
   // Migration options
   const [options, setOptions] = useState<MigrationOptions>({
     create_table_if_not_exists: true,
     truncate_before_insert: false,
+    recreate_table: false,
     disable_constraints: true,
     batch_size: 1000,
   });
@@ -159,6 +205,17 @@ function App() {
     };
   }, []);
 
+  // Collect CASCADE warnings of the running migration, to confirm them if it was stopped
+  useEffect(() => {
+    const unlisten = listen<MigrationWarning>("migration-warning", (event) => {
+      warningsRef.current.push(event.payload);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Fetch tables and schemas
   useEffect(() => {
     if (sourceConnection?.connected) {
@@ -182,7 +239,8 @@ function App() {
 
   const fetchTables = async (connectionId: string, setter: (tables: TableInfo[]) => void) => {
     try {
-      const tables = await invoke<TableInfo[]>("get_tables", { connectionId });
+      // Planner estimates keep the picker responsive on large databases
+      const tables = await invoke<TableInfo[]>("get_tables", { connectionId, estimated: true });
       setter(tables);
     } catch (error) {
       console.error("Failed to fetch tables:", error);
@@ -284,13 +342,17 @@ function App() {
     }
   };
 
-  const handleMigrate = async () => {
+  const handleMigrate = async (confirmDestructive = false) => {
     if (!sourceConnection || !targetConnection) return;
     if (selectedTables.size === 0) return;
 
+    const id = crypto.randomUUID();
+    setMigrationId(id);
     setIsMigrating(true);
     setProgress(null);
     setLastResult(null);
+    setDestructiveWarnings([]);
+    warningsRef.current = [];
 
     try {
       // Use the current order of sourceTables to determine migration order
@@ -303,13 +365,19 @@ function App() {
           source_connection_id: sourceConnection.id,
           target_connection_id: targetConnection.id,
           tables: tablesToMigrate,
-          options,
+          options: { ...options, confirm_destructive: confirmDestructive },
           target_schema_override: targetSchema.trim() || null,
+          migration_id: id,
         },
       });
 
       setLastResult(result);
 
+      // The backend refuses to drop or empty objects outside the selection until confirmed
+      if (!confirmDestructive && result.errors.some((e) => e.includes("confirm_destructive"))) {
+        setDestructiveWarnings(warningsRef.current);
+      }
+
       // Refresh target tables
       if (targetConnection) {
         fetchTables(targetConnection.id, setTargetTables);
@@ -320,18 +388,33 @@ function App() {
         success: false,
         tables_migrated: 0,
         total_rows: 0,
-        errors: [String(error)],
+        errors: [errorMessage(error)],
         elapsed_ms: 0,
+        table_results: [],
+        migration_id: id,
       });
     } finally {
+      setMigrationId(null);
       setIsMigrating(false);
+      setIsPaused(false);
       setProgress(null);
     }
   };
 
+  const handlePauseToggle = async () => {
+    if (!migrationId) return;
+    try {
+      await invoke(isPaused ? "resume_migration" : "pause_migration", { migrationId });
+      setIsPaused(!isPaused);
+    } catch (error) {
+      console.error("Failed to pause:", error);
+    }
+  };
+
   const handleCancel = async () => {
+    if (!migrationId) return;
     try {
-      await invoke("cancel_migration");
+      await invoke("cancel_migration", { migrationId });
     } catch (error) {
       console.error("Failed to cancel:", error);
     }
@@ -451,8 +534,13 @@ function App() {
         targetSchema={targetSchema}
         onTargetSchemaChange={setTargetSchema}
         schemas={targetSchemas}
-        onMigrate={handleMigrate}
+        onMigrate={() => handleMigrate()}
+        destructiveWarnings={destructiveWarnings}
+        onConfirmDestructive={() => handleMigrate(true)}
+        onDismissDestructive={() => setDestructiveWarnings([])}
         onCancel={handleCancel}
+        onPauseToggle={handlePauseToggle}
+        isPaused={isPaused}
         isMigrating={isMigrating}
         canMigrate={
           !!sourceConnection?.connected &&
@@ -1,5 +1,6 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../App";
 import type { ConnectionConfig, ConnectionStatus, SavedConnection } from "../App";
 
 interface ConnectionFormProps {
@@ -59,7 +60,7 @@ export function ConnectionForm({
                 });
             }
         } catch (err) {
-            setError(String(err));
+            setError(errorMessage(err));
         } finally {
             setIsConnecting(false);
         }
@@ -254,6 +255,15 @@ export function ConnectionForm({
                         </svg>
                     </button>
                 </div>
+                <label className="flex items-center gap-2 text-sm text-[var(--on-surface-variant)]">
+                    <input
+                        type="checkbox"
+                        checked={config.read_only ?? false}
+                        onChange={(e) => onConfigChange({ ...config, read_only: e.target.checked })}
+                        disabled={isConnected}
+                    />
+                    Read only
+                </label>
             </div>
 
             {/* Error */}
@@ -318,11 +328,20 @@ export function ConnectionForm({
 
             {/* Connected Info */}
             {isConnected && connection && (
-                <div className="mt-3 g-chip g-chip-success">
+                <div className="mt-3 g-chip g-chip-success" title={connection.server_version}>
                     <svg className="w-4 h-4 mr-1" fill="currentColor" viewBox="0 0 20 20">
                         <path fillRule="evenodd" d="M10 18a8 8 0 100-16 8 8 0 000 16zm3.707-9.293a1 1 0 00-1.414-1.414L9 10.586 7.707 9.293a1 1 0 00-1.414 1.414l2 2a1 1 0 001.414 0l4-4z" clipRule="evenodd" />
                     </svg>
-                    {connection.database}@{connection.host}
+                    {connection.current_user} · {connection.database}@{connection.host}
+                    {connection.read_only && " · read only"}
+                </div>
+            )}
+            {isConnected && connection?.warning && (
+                <div className="g-alert g-alert-warning mt-3">
+                    <svg className="w-5 h-5 flex-shrink-0" fill="currentColor" viewBox="0 0 20 20">
+                        <path fillRule="evenodd" d="M8.257 3.099c.765-1.36 2.722-1.36 3.486 0l5.58 9.92c.75 1.334-.213 2.98-1.742 2.98H4.42c-1.53 0-2.493-1.646-1.743-2.98l5.58-9.92zM11 13a1 1 0 11-2 0 1 1 0 012 0zm-1-8a1 1 0 00-1 1v3a1 1 0 002 0V6a1 1 0 00-1-1z" clipRule="evenodd" />
+                    </svg>
+                    <span>{connection.warning}</span>
                 </div>
             )}
         </div>
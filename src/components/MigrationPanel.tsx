@@ -1,4 +1,4 @@
-import type { MigrationOptions } from "../App";
+import type { MigrationOptions, MigrationWarning } from "../App";
 
 interface MigrationPanelProps {
     options: MigrationOptions;
@@ -7,7 +7,12 @@ interface MigrationPanelProps {
     onTargetSchemaChange: (val: string) => void;
     schemas: string[];
     onMigrate: () => void;
+    destructiveWarnings: MigrationWarning[];
+    onConfirmDestructive: () => void;
+    onDismissDestructive: () => void;
     onCancel: () => void;
+    onPauseToggle: () => void;
+    isPaused: boolean;
     isMigrating: boolean;
     canMigrate: boolean;
     selectedCount: number;
@@ -20,7 +25,12 @@ export function MigrationPanel({
     onTargetSchemaChange,
     schemas,
     onMigrate,
+    destructiveWarnings,
+    onConfirmDestructive,
+    onDismissDestructive,
     onCancel,
+    onPauseToggle,
+    isPaused,
     isMigrating,
     canMigrate,
     selectedCount,
@@ -93,6 +103,24 @@ export function MigrationPanel({
                             </span>
                         </label>
 
+                        {/* Recreate Option */}
+                        <label className="flex items-center gap-2 cursor-pointer group">
+                            <div
+                                className={`g-checkbox ${options.recreate_table ? 'checked' : ''}`}
+                                style={options.recreate_table ? { background: 'var(--google-red)', borderColor: 'var(--google-red)' } : {}}
+                                onClick={() => handleOptionChange("recreate_table", !options.recreate_table)}
+                            >
+                                {options.recreate_table && (
+                                    <svg className="w-3 h-3 text-white" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                                        <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={3} d="M5 13l4 4L19 7" />
+                                    </svg>
+                                )}
+                            </div>
+                            <span className="text-sm text-[var(--on-surface-variant)] group-hover:text-[var(--on-surface)]">
+                                Recreate
+                            </span>
+                        </label>
+
                         {/* Batch Size */}
                         <div className="flex items-center gap-2 border-l border-[var(--outline-variant)] pl-4">
                             <span className="text-xs font-medium text-[var(--on-surface-variant)]">Batch:</span>
@@ -112,6 +140,11 @@ export function MigrationPanel({
 
                 {/* Action Buttons */}
                 <div className="flex items-center gap-2">
+                    {isMigrating && (
+                        <button onClick={onPauseToggle} className="g-btn-outlined">
+                            {isPaused ? "Resume" : "Pause"}
+                        </button>
+                    )}
                     {isMigrating ? (
                         <button onClick={onCancel} className="g-btn-outlined" style={{ color: 'var(--google-red)', borderColor: 'var(--google-red)' }}>
                             <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
@@ -147,6 +180,51 @@ export function MigrationPanel({
                     <span><strong>Warning:</strong> Truncate will delete existing data in target tables.</span>
                 </div>
             )}
+
+            {/* Warning for Recreate */}
+            {options.recreate_table && (
+                <div className="g-alert g-alert-warning mt-4">
+                    <svg className="w-5 h-5 flex-shrink-0" fill="currentColor" viewBox="0 0 20 20">
+                        <path fillRule="evenodd" d="M8.257 3.099c.765-1.36 2.722-1.36 3.486 0l5.58 9.92c.75 1.334-.213 2.98-1.742 2.98H4.42c-1.53 0-2.493-1.646-1.743-2.98l5.58-9.92zM11 13a1 1 0 11-2 0 1 1 0 012 0zm-1-8a1 1 0 00-1 1v3a1 1 0 002 0V6a1 1 0 00-1-1z" clipRule="evenodd" />
+                    </svg>
+                    <span><strong>Warning:</strong> Recreate drops the target tables, their data and anything depending on them.</span>
+                </div>
+            )}
+
+            {/* Confirmation for CASCADE reaching objects outside the selection */}
+            {destructiveWarnings.length > 0 && !isMigrating && (
+                <div className="g-alert g-alert-error mt-4">
+                    <svg className="w-5 h-5 flex-shrink-0" fill="currentColor" viewBox="0 0 20 20">
+                        <path fillRule="evenodd" d="M8.257 3.099c.765-1.36 2.722-1.36 3.486 0l5.58 9.92c.75 1.334-.213 2.98-1.742 2.98H4.42c-1.53 0-2.493-1.646-1.743-2.98l5.58-9.92zM11 13a1 1 0 11-2 0 1 1 0 012 0zm-1-8a1 1 0 00-1 1v3a1 1 0 002 0V6a1 1 0 00-1-1z" clipRule="evenodd" />
+                    </svg>
+                    <div className="flex-1 space-y-2">
+                        {destructiveWarnings.map((warning) => (
+                            <div key={warning.message}>
+                                <span>{warning.message}</span>
+                                {warning.tables.length > 0 && (
+                                    <ul className="list-disc ml-5 text-xs">
+                                        {warning.tables.map((table) => (
+                                            <li key={table}>{table}</li>
+                                        ))}
+                                    </ul>
+                                )}
+                            </div>
+                        ))}
+                        <div className="flex gap-2">
+                            <button
+                                onClick={onConfirmDestructive}
+                                className="g-btn-filled py-2"
+                                style={{ backgroundColor: 'var(--google-red)' }}
+                            >
+                                Confirm and migrate
+                            </button>
+                            <button onClick={onDismissDestructive} className="g-btn-outlined py-2">
+                                Cancel
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            )}
         </div>
     );
 }
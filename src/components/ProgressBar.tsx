@@ -42,7 +42,11 @@ export function ProgressBar({ progress }: ProgressBarProps) {
                     )}
                     <div>
                         <p className="text-sm font-medium text-[var(--on-surface)]">
-                            {progress.status === "Complete" ? "Migration complete" : `Migrating: ${progress.table_name}`}
+                            {progress.status === "Complete"
+                                ? "Migration complete"
+                                : progress.status === "Paused"
+                                    ? `Paused: ${progress.table_name}`
+                                    : `Migrating: ${progress.table_name}`}
                         </p>
                         <p className="text-xs text-[var(--on-surface-variant)]">
                             Table {progress.current_table} of {progress.total_tables}
@@ -82,6 +82,8 @@ export function TableList({
         switch (status) {
             case "MATCH":
                 return <span title="Schema matches" className="text-green-500">✓</span>;
+            case "WARN":
+                return <span title={details || "Schema differs"} className="text-yellow-500">~</span>;
             case "MISSING_IN_TARGET":
                 return <span title="Missing in target" className="text-blue-500 text-xs px-1 border border-blue-500 rounded">NEW</span>;
             case "COLUMNS_MISMATCH":
@@ -221,11 +223,26 @@ export function TableList({
                                     </div>
                                     <div className="flex items-center gap-3 mt-0.5">
                                         <span className="text-[10px] text-[var(--on-surface-variant)]">
-                                            {formatNumber(table.row_count)} rows
+                                            {table.row_count_estimated ? "~" : ""}{formatNumber(table.row_count)} rows
                                         </span>
                                         <span className="text-[10px] text-[var(--on-surface-variant)]">
                                             {formatBytes(table.size_bytes)}
                                         </span>
+                                        {table.relation_kind === "materialized_view" && (
+                                            <span className="text-[10px] text-[var(--on-surface-variant)]">
+                                                materialized view
+                                            </span>
+                                        )}
+                                        {table.relation_kind === "partitioned_table" && (
+                                            <span className="text-[10px] text-[var(--on-surface-variant)]">
+                                                partitioned
+                                            </span>
+                                        )}
+                                        {table.relation_kind === "foreign_table" && (
+                                            <span className="text-[10px] text-[var(--on-surface-variant)]" title="Foreign tables can't be migrated, their rows live on another server">
+                                                foreign table
+                                            </span>
+                                        )}
                                     </div>
                                 </div>
                             </div>